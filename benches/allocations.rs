@@ -0,0 +1,115 @@
+//! Counts the heap allocations of the meshing steps, the first call on a thread fills the buffers
+//! that the next calls reuse.
+//!
+//! Run with `cargo bench --bench allocations`.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use bevy::prelude::*;
+use surface_nets_experiment::{
+    chunk::{ChunkKey, Extent3i, Sd8, PADDED_CHUNK_SIZE},
+    chunk_map::ChunkMap,
+    generation::{ChunkGenerator, GENERATOR},
+    meshing::{mesh_chunk, MesherKind, MeshingSettings},
+};
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(new_size, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// Same chunk as the `chunks` benchmarks
+const SURFACE_CHUNK: ChunkKey = ChunkKey(IVec3::new(8, 0, 0));
+
+const ITERATIONS: usize = 20;
+
+/// Allocations and allocated bytes of `f`
+fn count(mut f: impl FnMut()) -> (usize, usize) {
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let bytes = ALLOCATED_BYTES.load(Ordering::Relaxed);
+    f();
+    (
+        ALLOCATIONS.load(Ordering::Relaxed) - allocations,
+        ALLOCATED_BYTES.load(Ordering::Relaxed) - bytes,
+    )
+}
+
+fn report(step: &str, first: (usize, usize), next: (usize, usize)) {
+    println!(
+        "{step:<32} first call: {:>6} allocations, {:>9} bytes | next calls: {:>6} allocations, {:>9} bytes",
+        first.0,
+        first.1,
+        next.0 / ITERATIONS,
+        next.1 / ITERATIONS,
+    );
+}
+
+fn main() {
+    let chunk_map = ChunkMap::default();
+    let neighborhood = Extent3i::from_min_and_shape(SURFACE_CHUNK.0 - IVec3::ONE, IVec3::splat(3));
+    for key in neighborhood.iter3().map(ChunkKey::from) {
+        chunk_map
+            .storage
+            .insert(key, Arc::new(GENERATOR.generate_chunk(key)));
+    }
+
+    // The padded chunk is copied into a buffer owned by the caller, like the meshing tasks do
+    let mut padded_sdf = Box::new([Sd8::MAX; PADDED_CHUNK_SIZE]);
+    let mut copy = || {
+        chunk_map
+            .neighborhood(SURFACE_CHUNK)
+            .copy_to(&mut padded_sdf)
+    };
+    let first = count(&mut copy);
+    let next = count(|| (0..ITERATIONS).for_each(|_| copy()));
+    report("copy neighborhood", first, next);
+
+    for kind in MesherKind::ALL {
+        let settings = MeshingSettings {
+            mesher: kind,
+            ..default()
+        };
+        let mesher = settings.mesher();
+        let mesh = || {
+            mesh_chunk(
+                SURFACE_CHUNK,
+                &padded_sdf,
+                mesher.as_ref(),
+                GENERATOR.as_ref(),
+                &settings,
+                0,
+            );
+        };
+
+        let first = count(mesh);
+        let next = count(|| (0..ITERATIONS).for_each(|_| mesh()));
+        report(&format!("mesh_chunk ({})", kind.name()), first, next);
+    }
+}
@@ -0,0 +1,111 @@
+use std::sync::Arc;
+
+use bevy::prelude::*;
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use surface_nets_experiment::{
+    chunk::{ChunkKey, Extent3i},
+    chunk_map::{ChunkMap, SubBlockMask},
+    generation::{ChunkGenerator, GENERATOR},
+    meshing::{mesh_chunk, remesh_chunk_blocks, ChunkMeshBlocks, MesherKind, MeshingSettings},
+};
+
+/// A chunk crossing the surface of the planet, so that meshing has something to do
+const SURFACE_CHUNK: ChunkKey = ChunkKey(IVec3::new(8, 0, 0));
+
+fn surface_neighborhood() -> ChunkMap {
+    let chunk_map = ChunkMap::default();
+    let neighborhood = Extent3i::from_min_and_shape(SURFACE_CHUNK.0 - IVec3::ONE, IVec3::splat(3));
+
+    for key in neighborhood.iter3().map(ChunkKey::from) {
+        chunk_map
+            .storage
+            .insert(key, Arc::new(GENERATOR.generate_chunk(key)));
+    }
+
+    chunk_map
+}
+
+fn generate_chunk(c: &mut Criterion) {
+    c.bench_function("generate_chunk", |b| {
+        b.iter(|| GENERATOR.generate_chunk(black_box(SURFACE_CHUNK)))
+    });
+}
+
+fn copy_chunk_neighborhood(c: &mut Criterion) {
+    let chunk_map = surface_neighborhood();
+
+    c.bench_function("copy_chunk_neighborhood", |b| {
+        b.iter(|| chunk_map.copy_chunk_neighborhood(black_box(SURFACE_CHUNK)))
+    });
+}
+
+fn meshing(c: &mut Criterion) {
+    let padded_sdf = surface_neighborhood().copy_chunk_neighborhood(SURFACE_CHUNK);
+    let mut group = c.benchmark_group("mesh_chunk");
+
+    for kind in MesherKind::ALL {
+        let settings = MeshingSettings {
+            mesher: kind,
+            ..default()
+        };
+        let mesher = settings.mesher();
+
+        group.bench_function(BenchmarkId::from_parameter(kind.name()), |b| {
+            b.iter(|| {
+                mesh_chunk(
+                    SURFACE_CHUNK,
+                    black_box(&padded_sdf),
+                    mesher.as_ref(),
+                    GENERATOR.as_ref(),
+                    &settings,
+                    0,
+                )
+            })
+        });
+    }
+
+    group.finish();
+}
+
+/// Remeshing one sub-block of an edited chunk, compared to the whole chunk in `mesh_chunk`
+fn sub_block_remeshing(c: &mut Criterion) {
+    let padded_sdf = surface_neighborhood().copy_chunk_neighborhood(SURFACE_CHUNK);
+    let mut group = c.benchmark_group("remesh_chunk_blocks");
+
+    for kind in MesherKind::ALL {
+        let settings = MeshingSettings {
+            mesher: kind,
+            ..default()
+        };
+        let mesher = settings.mesher();
+
+        let mut blocks = ChunkMeshBlocks::default();
+        let remesh = |blocks: &mut ChunkMeshBlocks, dirty| {
+            remesh_chunk_blocks(
+                SURFACE_CHUNK,
+                black_box(&padded_sdf),
+                mesher.as_ref(),
+                GENERATOR.as_ref(),
+                &settings,
+                blocks,
+                dirty,
+            )
+        };
+        remesh(&mut blocks, SubBlockMask::ALL);
+
+        group.bench_function(BenchmarkId::from_parameter(kind.name()), |b| {
+            b.iter(|| remesh(&mut blocks, SubBlockMask(1)))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    generate_chunk,
+    copy_chunk_neighborhood,
+    meshing,
+    sub_block_remeshing
+);
+criterion_main!(benches);
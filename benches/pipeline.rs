@@ -0,0 +1,62 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use surface_nets_experiment::{
+    chunk::Extent3i,
+    generation::{GenerationPlugin, InitialChunksExtent},
+    meshing::{ChunkMeshStats, MeshingPlugin},
+    scheduling::{Scheduling, VoxelThreadingConfig},
+};
+
+/// Headless app running the generation and meshing plugins over a cube of `side³` chunks
+fn pipeline_app(side: i32, scheduling: Scheduling) -> App {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+        .add_asset::<Mesh>()
+        .add_asset::<StandardMaterial>()
+        .insert_resource(InitialChunksExtent(Extent3i::from_min_and_shape(
+            IVec3::splat(-side / 2),
+            IVec3::splat(side),
+        )))
+        .insert_resource(VoxelThreadingConfig {
+            scheduling,
+            ..default()
+        })
+        .add_plugins((GenerationPlugin { erosion: false }, MeshingPlugin));
+    app
+}
+
+fn run_until_meshed(app: &mut App, chunk_count: usize) {
+    let mut meshed_chunks = app.world.query_filtered::<(), With<ChunkMeshStats>>();
+
+    while meshed_chunks.iter(&app.world).count() < chunk_count {
+        app.update();
+    }
+}
+
+fn pipeline(c: &mut Criterion) {
+    let mut group = c.benchmark_group("pipeline");
+    group
+        .sample_size(10)
+        .measurement_time(Duration::from_secs(30));
+
+    for scheduling in Scheduling::ALL {
+        for side in [4, 8, 12] {
+            let chunk_count = (side * side * side) as usize;
+
+            group.bench_function(BenchmarkId::new(scheduling.name(), chunk_count), |b| {
+                b.iter_batched(
+                    || pipeline_app(side, scheduling),
+                    |mut app| run_until_meshed(&mut app, chunk_count),
+                    BatchSize::PerIteration,
+                )
+            });
+        }
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, pipeline);
+criterion_main!(benches);
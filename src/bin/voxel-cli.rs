@@ -0,0 +1,479 @@
+//! Generates and meshes a region of the world without opening a window, e.g. to reproduce the
+//! numbers of `benchmark.md` on machines without a GPU.
+
+use std::{
+    io,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use bevy::{
+    prelude::*,
+    tasks::{TaskPool, TaskPoolBuilder},
+};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use surface_nets_experiment::{
+    chunk::{ChunkKey, Extent3i, CHUNK_SIZE},
+    chunk_map::ChunkMap,
+    export::{write_to_file, ExportFormat, MergedMesh, WELD_TOLERANCE},
+    generation::{
+        world_generator, ChunkGenerator, CsgOperation, DensityVolume, Generator, Heightmap,
+        HeightmapGenerator, MeshSdf, MeshStamp, VolumeGenerator, WorldSeed, SPHERE_RADIUS,
+    },
+    meshing::{mesh_chunk, ChunkMeshStats, MesherKind, MeshingSettings},
+    LEVEL_OF_DETAIL,
+};
+
+#[derive(Parser)]
+#[command(about = "Generates and meshes a region of the world without a window")]
+struct Cli {
+    /// Number of worker threads, defaults to the available parallelism
+    #[arg(long, global = true)]
+    threads: Option<usize>,
+    /// Seed of the procedural world
+    #[arg(long, global = true, default_value_t = WorldSeed::default().0)]
+    seed: u64,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Generates the chunks of a region
+    Generate(RegionArgs),
+    /// Generates and meshes the chunks of a region
+    Mesh(MeshArgs),
+    /// Generates and meshes a region, then exports it to a single file
+    Export {
+        #[command(flatten)]
+        mesh: MeshArgs,
+        #[arg(long, value_enum, default_value_t = FormatArg::Obj)]
+        format: FormatArg,
+        /// Defaults to `terrain.<extension>`
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+        /// Merges the vertices duplicated along the seams between chunks
+        #[arg(long)]
+        weld: bool,
+    },
+    /// Generates and meshes a region several times and reports the timings
+    Bench {
+        #[command(flatten)]
+        mesh: MeshArgs,
+        #[arg(long, default_value_t = 5)]
+        iterations: u32,
+    },
+}
+
+#[derive(Args)]
+struct RegionArgs {
+    /// Range of chunk coordinates along every axis, the maximum is excluded (e.g. `-10..10`)
+    #[arg(long, default_value = "-10..10", value_parser = parse_extent, allow_hyphen_values = true)]
+    extent: Extent3i,
+    #[command(flatten)]
+    import: ImportArgs,
+}
+
+/// Replaces the procedural generator by imported data, or adds a mesh to it
+#[derive(Args)]
+struct ImportArgs {
+    /// Grayscale PNG or square 16-bit little-endian RAW heightmap
+    #[arg(long, conflicts_with = "volume")]
+    heightmap: Option<PathBuf>,
+    /// Projects the heightmap onto the planet, the image must be 6 square faces stacked vertically
+    #[arg(long, requires = "heightmap")]
+    cubemap: bool,
+    /// Voxels between two pixels of a flat heightmap
+    #[arg(long, default_value_t = 1.0)]
+    pixel_size: f32,
+    /// Height in voxels of the highest value of the heightmap
+    #[arg(long, default_value_t = 64.0)]
+    max_height: f32,
+    /// 8-bit RAW density volume or MagicaVoxel `.vox` model
+    #[arg(long)]
+    volume: Option<PathBuf>,
+    /// Size of a RAW volume (e.g. `64,32,64`)
+    #[arg(long, value_parser = parse_size)]
+    volume_size: Option<UVec3>,
+    /// Simulates the erosion of the procedural world, it takes a few seconds
+    #[arg(long, conflicts_with_all = ["heightmap", "volume"])]
+    erosion: bool,
+    /// Closed OBJ or binary glTF mesh stamped into the procedural world
+    #[arg(long, conflicts_with_all = ["heightmap", "volume"])]
+    prop: Option<PathBuf>,
+    /// Position of the origin of the prop in voxels (e.g. `0,270,0`)
+    #[arg(long, default_value = "0,0,0", value_parser = parse_position, allow_hyphen_values = true)]
+    prop_position: Vec3,
+    /// Voxels per unit of the prop mesh
+    #[arg(long, default_value_t = 1.0)]
+    prop_scale: f32,
+    /// Carves the prop out of the terrain instead of adding it
+    #[arg(long, requires = "prop")]
+    carve: bool,
+}
+
+impl ImportArgs {
+    fn generator(&self, seed: WorldSeed) -> io::Result<Arc<dyn ChunkGenerator>> {
+        if let Some(path) = &self.heightmap {
+            let heightmap = if has_extension(path, "raw") {
+                let side = ((std::fs::metadata(path)?.len() / 2) as f64).sqrt() as usize;
+                Heightmap::load_raw(path, side, side)?
+            } else {
+                Heightmap::load_png(path)?
+            };
+
+            return Ok(if self.cubemap {
+                Arc::new(HeightmapGenerator::cubemap(
+                    heightmap,
+                    SPHERE_RADIUS,
+                    self.max_height,
+                )?)
+            } else {
+                Arc::new(HeightmapGenerator::flat(
+                    heightmap,
+                    self.pixel_size,
+                    self.max_height,
+                ))
+            });
+        }
+
+        if let Some(path) = &self.volume {
+            let volume = if has_extension(path, "vox") {
+                DensityVolume::load_vox(path)?
+            } else {
+                let size = self.volume_size.ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::InvalidInput, "--volume-size is required")
+                })?;
+                DensityVolume::load_raw(path, size)?
+            };
+
+            return Ok(Arc::new(VolumeGenerator {
+                volume,
+                origin: Vec3::ZERO,
+                sample_size: 1.0,
+            }));
+        }
+
+        if !self.erosion && self.prop.is_none() {
+            return Ok(world_generator(seed));
+        }
+
+        let mut generator = if self.erosion {
+            Generator::for_world(seed)
+        } else {
+            Generator::new(seed)
+        };
+        if let Some(path) = &self.prop {
+            // Two samples per voxel of the world
+            let voxel_size = LEVEL_OF_DETAIL / (2.0 * self.prop_scale);
+            generator.props.push(MeshStamp {
+                sdf: Arc::new(MeshSdf::load(path, voxel_size)?),
+                translation: self.prop_position,
+                scale: self.prop_scale,
+                operation: if self.carve {
+                    CsgOperation::Subtraction
+                } else {
+                    CsgOperation::Union
+                },
+            });
+        }
+        Ok(Arc::new(generator))
+    }
+}
+
+fn has_extension(path: &Path, extension: &str) -> bool {
+    path.extension()
+        .map_or(false, |e| e.eq_ignore_ascii_case(extension))
+}
+
+#[derive(Args)]
+struct MeshArgs {
+    #[command(flatten)]
+    region: RegionArgs,
+    #[arg(long, value_enum, default_value_t = MesherArg::SurfaceNets)]
+    mesher: MesherArg,
+}
+
+impl MeshArgs {
+    fn settings(&self) -> MeshingSettings {
+        MeshingSettings {
+            mesher: match self.mesher {
+                MesherArg::SurfaceNets => MesherKind::SurfaceNets,
+                MesherArg::MarchingCubes => MesherKind::MarchingCubes,
+                MesherArg::DualContouring => MesherKind::DualContouring,
+            },
+            ..default()
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum MesherArg {
+    SurfaceNets,
+    MarchingCubes,
+    DualContouring,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum FormatArg {
+    Obj,
+    Ply,
+    Stl,
+    Gltf,
+}
+
+impl From<FormatArg> for ExportFormat {
+    fn from(format: FormatArg) -> Self {
+        match format {
+            FormatArg::Obj => Self::Obj,
+            FormatArg::Ply => Self::Ply,
+            FormatArg::Stl => Self::Stl,
+            FormatArg::Gltf => Self::Gltf,
+        }
+    }
+}
+
+fn parse_extent(s: &str) -> Result<Extent3i, String> {
+    let (min, lub) = s
+        .split_once("..")
+        .ok_or_else(|| format!("expected `min..max`, got `{s}`"))?;
+    let min: i32 = min
+        .trim()
+        .parse()
+        .map_err(|e| format!("invalid minimum: {e}"))?;
+    let lub: i32 = lub
+        .trim()
+        .parse()
+        .map_err(|e| format!("invalid maximum: {e}"))?;
+
+    if lub <= min {
+        return Err(format!("empty range `{s}`"));
+    }
+
+    Ok(Extent3i::from_min_and_lub(
+        IVec3::splat(min),
+        IVec3::splat(lub),
+    ))
+}
+
+fn parse_position(s: &str) -> Result<Vec3, String> {
+    let components = s
+        .split(',')
+        .map(|c| {
+            c.trim()
+                .parse::<f32>()
+                .map_err(|e| format!("invalid coordinate: {e}"))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    match components[..] {
+        [x, y, z] => Ok(Vec3::new(x, y, z)),
+        _ => Err(format!("expected three coordinates `x,y,z`, got `{s}`")),
+    }
+}
+
+fn parse_size(s: &str) -> Result<UVec3, String> {
+    let components = s
+        .split(',')
+        .map(|c| {
+            c.trim()
+                .parse::<u32>()
+                .map_err(|e| format!("invalid size: {e}"))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    match components[..] {
+        [x, y, z] if x > 0 && y > 0 && z > 0 => Ok(UVec3::new(x, y, z)),
+        _ => Err(format!("expected three positive sizes `x,y,z`, got `{s}`")),
+    }
+}
+
+fn main() {
+    let cli = Cli::parse();
+
+    let threads = cli
+        .threads
+        .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |n| n.get()));
+    let pool = TaskPoolBuilder::default()
+        .num_threads(threads)
+        .thread_name(format!("voxel-cli ({threads} threads)"))
+        .build();
+
+    println!("Using {threads} threads");
+
+    let seed = WorldSeed(cli.seed);
+
+    match cli.command {
+        Command::Generate(region) => {
+            let generator = load_generator(&region.import, seed);
+            generate(&pool, &region.extent, generator.as_ref());
+        }
+        Command::Mesh(args) => {
+            let generator = load_generator(&args.region.import, seed);
+            let chunk_map = generate(&pool, &args.region.extent, generator.as_ref());
+            mesh(&pool, &chunk_map, generator.as_ref(), &args.settings());
+        }
+        Command::Export {
+            mesh: args,
+            format,
+            output,
+            weld,
+        } => {
+            let generator = load_generator(&args.region.import, seed);
+            let chunk_map = generate(&pool, &args.region.extent, generator.as_ref());
+            let meshes = mesh(&pool, &chunk_map, generator.as_ref(), &args.settings());
+
+            let mut merged = MergedMesh::default();
+            for (key, mesh, _) in &meshes {
+                merged.append(
+                    mesh,
+                    key.min_point().as_vec3() * LEVEL_OF_DETAIL,
+                    LEVEL_OF_DETAIL,
+                );
+            }
+            if weld {
+                merged.weld(WELD_TOLERANCE);
+            }
+
+            let format = ExportFormat::from(format);
+            let path = output.unwrap_or_else(|| format!("terrain.{}", format.extension()).into());
+            match write_to_file(&merged, format, &path) {
+                Ok(()) => println!(
+                    "Exported {} triangles to {}",
+                    merged.triangle_count(),
+                    path.display()
+                ),
+                Err(e) => {
+                    eprintln!("Failed to export to {}: {e}", path.display());
+                    std::process::exit(1);
+                }
+            }
+        }
+        Command::Bench {
+            mesh: args,
+            iterations,
+        } => bench(&pool, &args, seed, iterations),
+    }
+}
+
+fn generate(pool: &TaskPool, extent: &Extent3i, generator: &dyn ChunkGenerator) -> ChunkMap {
+    let (chunk_map, elapsed) = timed_generate(pool, extent, generator);
+    report("generate", chunk_map.storage.len(), elapsed);
+    chunk_map
+}
+
+fn mesh(
+    pool: &TaskPool,
+    chunk_map: &ChunkMap,
+    generator: &dyn ChunkGenerator,
+    settings: &MeshingSettings,
+) -> Vec<(ChunkKey, Mesh, ChunkMeshStats)> {
+    let (meshes, elapsed) = timed_mesh(pool, chunk_map, generator, settings);
+    report("mesh", chunk_map.storage.len(), elapsed);
+
+    let triangles: usize = meshes.iter().map(|(_, _, s)| s.final_triangles).sum();
+    println!(
+        "{} chunks had a surface, {triangles} triangles in total",
+        meshes.len()
+    );
+
+    meshes
+}
+
+fn load_generator(import: &ImportArgs, seed: WorldSeed) -> Arc<dyn ChunkGenerator> {
+    import.generator(seed).unwrap_or_else(|e| {
+        eprintln!("Failed to import: {e}");
+        std::process::exit(1);
+    })
+}
+
+fn bench(pool: &TaskPool, args: &MeshArgs, seed: WorldSeed, iterations: u32) {
+    let settings = args.settings();
+    let generator = load_generator(&args.region.import, seed);
+    let mut generation_times = Vec::new();
+    let mut meshing_times = Vec::new();
+    let mut chunk_count = 0;
+
+    for i in 0..iterations {
+        let (chunk_map, generation_time) =
+            timed_generate(pool, &args.region.extent, generator.as_ref());
+        let (_, meshing_time) = timed_mesh(pool, &chunk_map, generator.as_ref(), &settings);
+        chunk_count = chunk_map.storage.len();
+
+        println!(
+            "Iteration {}/{iterations}: generation {generation_time:?}, meshing {meshing_time:?}",
+            i + 1
+        );
+        generation_times.push(generation_time);
+        meshing_times.push(meshing_time);
+    }
+
+    for (step, times) in [("generation", generation_times), ("meshing", meshing_times)] {
+        let min = times.iter().min().copied().unwrap_or_default();
+        let max = times.iter().max().copied().unwrap_or_default();
+        let mean = times.iter().sum::<Duration>() / iterations.max(1);
+        println!(
+            "{step}: min {min:?}, mean {mean:?}, max {max:?} for {chunk_count} chunks ({:?} / chunk)",
+            mean / chunk_count.max(1) as u32
+        );
+    }
+}
+
+fn timed_generate(
+    pool: &TaskPool,
+    extent: &Extent3i,
+    generator: &dyn ChunkGenerator,
+) -> (ChunkMap, Duration) {
+    let chunk_map = ChunkMap::default();
+
+    // The tasks insert their chunk in the shared store themselves
+    let start = Instant::now();
+    pool.scope(|s| {
+        for key in extent.iter3().map(ChunkKey::from) {
+            let chunk_map = &chunk_map;
+            s.spawn(async move {
+                let chunk = generator.generate_chunk(key);
+                chunk_map.storage.insert(key, Arc::new(chunk));
+            });
+        }
+    });
+    let elapsed = start.elapsed();
+
+    (chunk_map, elapsed)
+}
+
+fn timed_mesh(
+    pool: &TaskPool,
+    chunk_map: &ChunkMap,
+    generator: &dyn ChunkGenerator,
+    settings: &MeshingSettings,
+) -> (Vec<(ChunkKey, Mesh, ChunkMeshStats)>, Duration) {
+    let mesher = settings.mesher();
+
+    let start = Instant::now();
+    let meshes = pool.scope(|s| {
+        for key in chunk_map.storage.keys() {
+            let mesher = &mesher;
+            s.spawn(async move {
+                let padded_sdf = chunk_map.copy_chunk_neighborhood(key);
+                mesh_chunk(key, &padded_sdf, mesher.as_ref(), generator, settings, 0)
+                    .map(|(mesh, stats)| (key, mesh, stats))
+            });
+        }
+    });
+    let elapsed = start.elapsed();
+
+    (meshes.into_iter().flatten().collect(), elapsed)
+}
+
+fn report(step: &str, chunk_count: usize, elapsed: Duration) {
+    let per_chunk = elapsed / chunk_count.max(1) as u32;
+    let throughput = chunk_count as f64 / elapsed.as_secs_f64();
+    let points = chunk_count * CHUNK_SIZE;
+
+    println!(
+        "took {elapsed:?} to {step} {chunk_count} chunks ({points} points, {per_chunk:?} / chunk, {throughput:.1} chunks/s)"
+    );
+}
@@ -61,6 +61,138 @@ impl Chunk {
         let index = ChunkShape::linearize(offset.as_uvec3().to_array()) as usize;
         self.sdf[index] = sd;
     }
+
+    pub fn get_voxel(&self, offset: IVec3) -> Sd8 {
+        let index = ChunkShape::linearize(offset.as_uvec3().to_array()) as usize;
+        self.sdf[index]
+    }
+}
+
+/// Whether voxels are in the air, in the matter or both. The voxels on the surface count as air.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+pub enum Fill {
+    Empty,
+    Solid,
+    Mixed,
+}
+
+impl Fill {
+    fn from_range(min: Sd8, max: Sd8) -> Self {
+        match (min.0 >= 0, max.0 < 0) {
+            (true, _) => Self::Empty,
+            (_, true) => Self::Solid,
+            _ => Self::Mixed,
+        }
+    }
+}
+
+/// What a chunk contains, to skip it without reading its voxels
+#[derive(Debug, Clone, Copy)]
+pub struct ChunkSummary {
+    pub min: Sd8,
+    pub max: Sd8,
+    /// Fill of the layer of voxels along each face, indexed by [`Face`]
+    pub faces: [Fill; 6],
+}
+
+impl ChunkSummary {
+    /// Summary of a chunk that is only air
+    pub const EMPTY: Self = Self {
+        min: Sd8::MAX,
+        max: Sd8::MAX,
+        faces: [Fill::Empty; 6],
+    };
+
+    pub fn from_chunk(chunk: &Chunk) -> Self {
+        let (min, max) = chunk.sdf.iter().fold((i8::MAX, i8::MIN), |(min, max), sd| {
+            (min.min(sd.0), max.max(sd.0))
+        });
+
+        let faces = Face::ALL.map(|face| {
+            let axis = face.axis();
+            let layer = if face.offset()[axis] < 0 {
+                0
+            } else {
+                CHUNK_SIDE - 1
+            };
+            let (u, v) = ((axis + 1) % 3, (axis + 2) % 3);
+
+            let (mut min, mut max) = (i8::MAX, i8::MIN);
+            for a in 0..CHUNK_SIDE {
+                for b in 0..CHUNK_SIDE {
+                    let mut p = [0; 3];
+                    p[axis] = layer;
+                    p[u] = a;
+                    p[v] = b;
+                    let sd = chunk.sdf[ChunkShape::linearize(p) as usize].0;
+                    (min, max) = (min.min(sd), max.max(sd));
+                }
+            }
+            Fill::from_range(Sd8(min), Sd8(max))
+        });
+
+        Self {
+            min: Sd8(min),
+            max: Sd8(max),
+            faces,
+        }
+    }
+
+    pub fn fill(&self) -> Fill {
+        Fill::from_range(self.min, self.max)
+    }
+
+    pub fn face(&self, face: Face) -> Fill {
+        self.faces[face as usize]
+    }
+
+    /// Whether the chunk has voxels on both sides of the surface, only those can be meshed
+    pub fn crosses_surface(&self) -> bool {
+        self.fill() == Fill::Mixed
+    }
+}
+
+/// Face of a chunk, in the order of [`Face::ALL`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Face {
+    NegX,
+    PosX,
+    NegY,
+    PosY,
+    NegZ,
+    PosZ,
+}
+
+impl Face {
+    pub const ALL: [Self; 6] = [
+        Self::NegX,
+        Self::PosX,
+        Self::NegY,
+        Self::PosY,
+        Self::NegZ,
+        Self::PosZ,
+    ];
+
+    /// Offset to the chunk behind the face
+    pub fn offset(self) -> IVec3 {
+        match self {
+            Self::NegX => IVec3::NEG_X,
+            Self::PosX => IVec3::X,
+            Self::NegY => IVec3::NEG_Y,
+            Self::PosY => IVec3::Y,
+            Self::NegZ => IVec3::NEG_Z,
+            Self::PosZ => IVec3::Z,
+        }
+    }
+
+    pub fn opposite(self) -> Self {
+        Self::ALL[self as usize ^ 1]
+    }
+
+    /// Axis the face is orthogonal to
+    pub fn axis(self) -> usize {
+        self as usize / 2
+    }
 }
 
 #[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash, Deref, DerefMut, Reflect)]
@@ -76,6 +208,11 @@ impl ChunkKey {
     pub fn extent(&self) -> Extent3i {
         Extent3i::from_min_and_shape(self.min_point(), CHUNK_SHAPE)
     }
+
+    /// Chunk containing a voxel
+    pub fn from_voxel(p: IVec3) -> Self {
+        Self(p >> CHUNK_SHAPE_LOG2)
+    }
 }
 
 impl From<IVec3> for ChunkKey {
@@ -1,60 +1,299 @@
-use std::vec::Drain;
-
-use bevy::{
-    prelude::*,
-    utils::{HashMap, HashSet},
+use std::{
+    collections::BTreeMap,
+    fmt,
+    ops::{BitOr, BitOrAssign},
+    sync::{Arc, RwLock},
+    vec::Drain,
 };
-use float_ord::FloatOrd;
+
+use bevy::{prelude::*, utils::HashMap};
 use tracing::instrument;
 
-use crate::chunk::{
-    Chunk, ChunkKey, ChunkShape, Extent3i, PaddedChunkShape, Sd8, CHUNK_SHAPE_LOG2,
-    PADDED_CHUNK_SHAPE, PADDED_CHUNK_SIZE,
+use crate::{
+    chunk::{
+        Chunk, ChunkKey, ChunkShape, ChunkSummary, Extent3i, Fill, PaddedChunkShape, Sd8,
+        CHUNK_SHAPE_LOG2, CHUNK_SIDE, PADDED_CHUNK_SHAPE, PADDED_CHUNK_SIZE,
+    },
+    culling::FaceConnectivity,
 };
 
-#[derive(Resource, Default)]
+/// Loaded chunks. Cloning the map gives another handle to the same store, so that the tasks can
+/// read the chunks in parallel with the main thread.
+#[derive(Resource, Default, Clone)]
 pub struct ChunkMap {
-    pub storage: HashMap<ChunkKey, Chunk>,
+    pub storage: Arc<ChunkStore>,
 }
 
 impl ChunkMap {
-    #[instrument(skip_all, level = "trace")]
-    pub fn copy_chunk_neighborhood(&self, key: ChunkKey) -> [Sd8; PADDED_CHUNK_SIZE] {
+    /// The loaded chunks overlapping the padded chunk of `key`, to copy its voxels off the main
+    /// thread
+    pub fn neighborhood(&self, key: ChunkKey) -> ChunkNeighborhood {
         let padded_chunk_extent = key.extent().with_shape(PADDED_CHUNK_SHAPE);
-        let mut neighborhood = [Sd8::MAX; PADDED_CHUNK_SIZE];
-
-        chunks_in_extent(&padded_chunk_extent)
-            .filter_map(|chunk_key| {
-                let chunk_extent = chunk_key.extent();
-                let intersection = padded_chunk_extent.intersection(&chunk_extent);
-
-                self.storage
-                    .get(&chunk_key)
-                    .map(|chunk| (chunk_key, intersection, chunk.sdf))
-            })
-            .map(|(chunk_key, extent, sdf)| {
-                let copy_shape = extent.shape.as_uvec3().to_array();
-                let src_start = (extent.minimum - chunk_key.min_point())
-                    .as_uvec3()
-                    .to_array();
-                let dst_start = (extent.minimum - padded_chunk_extent.minimum)
-                    .as_uvec3()
-                    .to_array();
-                (copy_shape, sdf, src_start, dst_start)
-            })
-            .for_each(|(copy_shape, sdf, src_start, dst_start)| {
-                ndcopy::copy3(
-                    copy_shape,
-                    &sdf,
-                    &ChunkShape {},
-                    src_start,
-                    &mut neighborhood,
-                    &PaddedChunkShape {},
-                    dst_start,
-                );
-            });
 
-        neighborhood
+        ChunkNeighborhood {
+            key,
+            chunks: chunks_in_extent(&padded_chunk_extent)
+                .filter_map(|k| self.storage.get(&k).map(|chunk| (k, chunk)))
+                .collect(),
+        }
+    }
+
+    pub fn copy_chunk_neighborhood(&self, key: ChunkKey) -> [Sd8; PADDED_CHUNK_SIZE] {
+        let mut padded_sdf = [Sd8::MAX; PADDED_CHUNK_SIZE];
+        self.neighborhood(key).copy_to(&mut padded_sdf);
+        padded_sdf
+    }
+
+    /// Inserts a generated chunk and marks it dirty, along with the loaded chunks whose padding it
+    /// fills: they may have been meshed while it wasn't requested yet
+    pub fn insert_generated(
+        &self,
+        key: ChunkKey,
+        chunk: Chunk,
+        summary: ChunkSummary,
+        dirty_chunks: &mut DirtyChunks,
+    ) {
+        self.storage
+            .insert_summarized(key, Arc::new(chunk), summary);
+        dirty_chunks.insert(key);
+        dirty_chunks.extend(padding_dependents(key).filter(|k| self.storage.contains_key(k)));
+    }
+
+    /// A chunk can be meshed once it's generated and each of its padding neighbors is either
+    /// generated or not requested. The chunks that aren't requested are meshed as air, and the
+    /// chunk is remeshed if they're generated later, see [`Self::insert_generated`].
+    pub fn is_ready_to_mesh(&self, key: ChunkKey, current_chunks: &CurrentChunks) -> bool {
+        self.storage.contains_key(&key)
+            && padding_neighbors(key)
+                .all(|k| self.storage.contains_key(&k) || !current_chunks.contains(k))
+    }
+
+    /// Whether the padded chunk of `key` is only air or only matter, so that its mesh is empty. The
+    /// chunks that aren't loaded are air.
+    pub fn is_neighborhood_uniform(&self, key: ChunkKey) -> bool {
+        let fill = |k: &ChunkKey| self.storage.summary(k).map_or(Fill::Empty, |s| s.fill());
+        let first = fill(&key);
+        first != Fill::Mixed && padding_neighbors(key).all(|k| fill(&k) == first)
+    }
+
+    /// Connectivity of the faces of a chunk, only flood filled if it has both air and matter. The
+    /// chunks that aren't loaded are air.
+    pub fn face_connectivity(&self, key: ChunkKey) -> FaceConnectivity {
+        match self.storage.summary(&key).map(|summary| summary.fill()) {
+            None | Some(Fill::Empty) => FaceConnectivity::ALL,
+            Some(Fill::Solid) => FaceConnectivity::NONE,
+            Some(Fill::Mixed) => self
+                .storage
+                .get(&key)
+                .map_or(FaceConnectivity::ALL, |chunk| {
+                    FaceConnectivity::from_chunk(&chunk)
+                }),
+        }
+    }
+
+    /// Signed distance of a voxel, `None` if its chunk isn't loaded
+    pub fn get_voxel(&self, p: IVec3) -> Option<Sd8> {
+        let key = ChunkKey::from_voxel(p);
+        self.storage
+            .read(&key, |chunk| chunk.get_voxel(p - key.min_point()))
+    }
+
+    /// Trilinear interpolation of the signed distance at a point in voxel space.
+    ///
+    /// Like the stored values, the result is clamped to one voxel away from the surface.
+    pub fn sample_distance(&self, p: Vec3) -> Option<f32> {
+        let min = p.floor();
+        let t = p - min;
+        let min = min.as_ivec3();
+
+        let mut distance = 0.0;
+        for z in 0..2 {
+            for y in 0..2 {
+                for x in 0..2 {
+                    let corner = IVec3::new(x, y, z);
+                    let weights = Vec3::ONE - corner.as_vec3() + (2.0 * corner.as_vec3() - 1.0) * t;
+                    let d: f32 = self.get_voxel(min + corner)?.into();
+                    distance += d * weights.x * weights.y * weights.z;
+                }
+            }
+        }
+
+        Some(distance)
+    }
+
+    /// Gradient of the interpolated signed distance, using central differences
+    pub fn sample_gradient(&self, p: Vec3) -> Option<Vec3> {
+        const H: f32 = 0.5;
+
+        let mut gradient = Vec3::ZERO;
+        for axis in 0..3 {
+            let mut offset = Vec3::ZERO;
+            offset[axis] = H;
+            gradient[axis] =
+                (self.sample_distance(p + offset)? - self.sample_distance(p - offset)?) / (2.0 * H);
+        }
+
+        Some(gradient)
+    }
+}
+
+const SHARD_COUNT: usize = 16;
+
+type Shard = RwLock<HashMap<ChunkKey, (Arc<Chunk>, ChunkSummary)>>;
+
+/// Map of the chunks split into shards locked separately, so that threads reading different
+/// chunks rarely wait on each other. The reads aren't lock-free, every lookup takes the read lock
+/// of a shard, but readers only hold it while cloning the `Arc` of a chunk or copying its summary.
+/// An edited chunk is copied if another thread still holds it.
+///
+/// The [`ChunkSummary`] of each chunk is stored with it and kept up to date by the edits.
+pub struct ChunkStore {
+    shards: [Shard; SHARD_COUNT],
+}
+
+impl Default for ChunkStore {
+    fn default() -> Self {
+        Self {
+            shards: std::array::from_fn(|_| RwLock::default()),
+        }
+    }
+}
+
+impl ChunkStore {
+    /// Spreads the chunks over the shards with a hash of their coordinates, neighboring chunks
+    /// usually land in different shards but nothing guarantees it
+    fn shard(&self, key: &ChunkKey) -> &Shard {
+        let [x, y, z] = key.to_array().map(|c| c as u32);
+        let hash =
+            x.wrapping_mul(0x9e37_79b1) ^ y.wrapping_mul(0x85eb_ca6b) ^ z.wrapping_mul(0xc2b2_ae35);
+        &self.shards[(hash >> (u32::BITS - SHARD_COUNT.ilog2())) as usize]
+    }
+
+    pub fn get(&self, key: &ChunkKey) -> Option<Arc<Chunk>> {
+        self.shard(key)
+            .read()
+            .unwrap()
+            .get(key)
+            .map(|(chunk, _)| Arc::clone(chunk))
+    }
+
+    pub fn summary(&self, key: &ChunkKey) -> Option<ChunkSummary> {
+        self.shard(key)
+            .read()
+            .unwrap()
+            .get(key)
+            .map(|&(_, summary)| summary)
+    }
+
+    /// Reads a chunk without cloning its `Arc`, the shard stays locked meanwhile
+    pub fn read<R>(&self, key: &ChunkKey, f: impl FnOnce(&Chunk) -> R) -> Option<R> {
+        self.shard(key)
+            .read()
+            .unwrap()
+            .get(key)
+            .map(|(chunk, _)| f(chunk))
+    }
+
+    pub fn contains_key(&self, key: &ChunkKey) -> bool {
+        self.shard(key).read().unwrap().contains_key(key)
+    }
+
+    pub fn insert(&self, key: ChunkKey, chunk: Arc<Chunk>) -> Option<Arc<Chunk>> {
+        let summary = ChunkSummary::from_chunk(&chunk);
+        self.insert_summarized(key, chunk, summary)
+    }
+
+    /// Inserts a chunk whose summary was computed elsewhere, like in the generation tasks
+    pub fn insert_summarized(
+        &self,
+        key: ChunkKey,
+        chunk: Arc<Chunk>,
+        summary: ChunkSummary,
+    ) -> Option<Arc<Chunk>> {
+        let mut shard = self.shard(&key).write().unwrap();
+        shard.insert(key, (chunk, summary)).map(|(chunk, _)| chunk)
+    }
+
+    pub fn remove(&self, key: &ChunkKey) -> Option<Arc<Chunk>> {
+        let mut shard = self.shard(key).write().unwrap();
+        shard.remove(key).map(|(chunk, _)| chunk)
+    }
+
+    /// Edits a chunk in place, or a copy of it if another thread still holds it, and updates its
+    /// summary. Returns `None` if the chunk isn't loaded.
+    pub fn edit<R>(&self, key: &ChunkKey, f: impl FnOnce(&mut Chunk) -> R) -> Option<R> {
+        let mut shard = self.shard(key).write().unwrap();
+        shard.get_mut(key).map(|(chunk, summary)| {
+            let chunk = Arc::make_mut(chunk);
+            let result = f(chunk);
+            *summary = ChunkSummary::from_chunk(chunk);
+            result
+        })
+    }
+
+    /// Keys of the loaded chunks, the chunks inserted or removed meanwhile by other threads may
+    /// be missing or included
+    pub fn keys(&self) -> Vec<ChunkKey> {
+        self.shards
+            .iter()
+            .flat_map(|shard| shard.read().unwrap().keys().copied().collect::<Vec<_>>())
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.shards
+            .iter()
+            .map(|shard| shard.read().unwrap().len())
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// The loaded chunks whose voxels fill the padded chunk of `key`
+#[derive(Debug, Clone)]
+pub struct ChunkNeighborhood {
+    key: ChunkKey,
+    chunks: Vec<(ChunkKey, Arc<Chunk>)>,
+}
+
+impl ChunkNeighborhood {
+    pub fn key(&self) -> ChunkKey {
+        self.key
+    }
+
+    /// Copies the voxels into the padded chunk, the voxels of the missing chunks are air
+    #[instrument(skip_all, level = "trace")]
+    pub fn copy_to(&self, padded_sdf: &mut [Sd8; PADDED_CHUNK_SIZE]) {
+        let padded_chunk_extent = self.key.extent().with_shape(PADDED_CHUNK_SHAPE);
+
+        // The padded chunk always overlaps 8 chunks
+        if self.chunks.len() < 8 {
+            padded_sdf.fill(Sd8::MAX);
+        }
+
+        for (chunk_key, chunk) in &self.chunks {
+            let extent = padded_chunk_extent.intersection(&chunk_key.extent());
+            let copy_shape = extent.shape.as_uvec3().to_array();
+            let src_start = (extent.minimum - chunk_key.min_point())
+                .as_uvec3()
+                .to_array();
+            let dst_start = (extent.minimum - padded_chunk_extent.minimum)
+                .as_uvec3()
+                .to_array();
+
+            ndcopy::copy3(
+                copy_shape,
+                &chunk.sdf,
+                &ChunkShape {},
+                src_start,
+                padded_sdf,
+                &PaddedChunkShape {},
+                dst_start,
+            );
+        }
     }
 }
 
@@ -72,11 +311,6 @@ impl ChunkCommandQueue {
         }
     }
 
-    pub fn sort_by_distance(&mut self, key: ChunkKey) {
-        self.create
-            .sort_unstable_by_key(|k| FloatOrd(k.as_vec3().distance_squared(key.as_vec3())));
-    }
-
     pub fn is_create_empty(&self) -> bool {
         self.create.is_empty()
     }
@@ -108,24 +342,291 @@ pub enum ChunkCommand {
 }
 
 #[derive(Resource, Default)]
-pub struct CurrentChunks(HashMap<ChunkKey, Entity>);
+pub struct CurrentChunks {
+    entities: HashMap<ChunkKey, Entity>,
+    /// Number of chunks at each coordinate of each axis, so that the bounds are known without
+    /// scanning the chunks
+    coordinates: [BTreeMap<i32, usize>; 3],
+}
 
 impl CurrentChunks {
     pub fn add(&mut self, key: ChunkKey, entity: Entity) {
-        self.0.insert(key, entity);
+        if self.entities.insert(key, entity).is_none() {
+            for (axis, c) in self.coordinates.iter_mut().zip(key.to_array()) {
+                *axis.entry(c).or_default() += 1;
+            }
+        }
     }
 
     pub fn get_entity(&self, key: ChunkKey) -> Option<Entity> {
-        self.0.get(&key).copied()
+        self.entities.get(&key).copied()
     }
 
     pub fn contains(&self, key: ChunkKey) -> bool {
-        self.0.contains_key(&key)
+        self.entities.contains_key(&key)
     }
+
+    pub fn remove(&mut self, key: ChunkKey) -> Option<Entity> {
+        let entity = self.entities.remove(&key)?;
+        for (axis, c) in self.coordinates.iter_mut().zip(key.to_array()) {
+            let count = axis.get_mut(&c).unwrap();
+            *count -= 1;
+            if *count == 0 {
+                axis.remove(&c);
+            }
+        }
+        Some(entity)
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = ChunkKey> + '_ {
+        self.entities.keys().copied()
+    }
+
+    /// Minimum and maximum keys of the chunks, `None` if there isn't any
+    pub fn bounds(&self) -> Option<(IVec3, IVec3)> {
+        let [x, y, z] = &self.coordinates;
+        let min = IVec3::new(
+            *x.first_key_value()?.0,
+            *y.first_key_value()?.0,
+            *z.first_key_value()?.0,
+        );
+        let max = IVec3::new(
+            *x.last_key_value()?.0,
+            *y.last_key_value()?.0,
+            *z.last_key_value()?.0,
+        );
+        Some((min, max))
+    }
+}
+
+/// Chunks waiting to be meshed, with the sub-blocks that changed since their last mesh
+#[derive(Resource, Default)]
+pub struct DirtyChunks(HashMap<ChunkKey, SubBlockMask>);
+
+impl DirtyChunks {
+    /// Marks the whole chunk dirty
+    pub fn insert(&mut self, key: ChunkKey) {
+        self.0.insert(key, SubBlockMask::ALL);
+    }
+
+    /// Marks some sub-blocks of the chunk dirty, added to the ones already dirty
+    pub fn insert_blocks(&mut self, key: ChunkKey, blocks: SubBlockMask) {
+        *self.0.entry(key).or_default() |= blocks;
+    }
+
+    pub fn remove(&mut self, key: &ChunkKey) -> Option<SubBlockMask> {
+        self.0.remove(key)
+    }
+
+    pub fn contains(&self, key: &ChunkKey) -> bool {
+        self.0.contains_key(key)
+    }
+
+    pub fn get(&self, key: &ChunkKey) -> Option<SubBlockMask> {
+        self.0.get(key).copied()
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &ChunkKey> {
+        self.0.keys()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&ChunkKey, &SubBlockMask)> {
+        self.0.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl Extend<ChunkKey> for DirtyChunks {
+    fn extend<T: IntoIterator<Item = ChunkKey>>(&mut self, keys: T) {
+        keys.into_iter().for_each(|key| self.insert(key));
+    }
+}
+
+/// Side of the sub-blocks of a chunk that are meshed separately after an edit, in voxels
+pub const SUB_BLOCK_SIDE: u32 = 8;
+pub const SUB_BLOCKS_PER_SIDE: u32 = CHUNK_SIDE / SUB_BLOCK_SIDE;
+pub const SUB_BLOCK_COUNT: usize = SUB_BLOCKS_PER_SIDE.pow(3) as usize;
+
+const _: () = assert!(SUB_BLOCK_COUNT <= u64::BITS as usize);
+
+/// Set of the sub-blocks of a chunk, the bit `x + 4 * (y + 4 * z)` is the sub-block at `(x, y, z)`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SubBlockMask(pub u64);
+
+impl SubBlockMask {
+    pub const EMPTY: Self = Self(0);
+    pub const ALL: Self = Self(u64::MAX >> (u64::BITS as usize - SUB_BLOCK_COUNT));
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn is_all(self) -> bool {
+        self == Self::ALL
+    }
+
+    pub fn len(self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    /// Indices of the sub-blocks in the set
+    pub fn iter(self) -> impl Iterator<Item = usize> {
+        (0..SUB_BLOCK_COUNT).filter(move |&i| self.0 & (1 << i) != 0)
+    }
+
+    /// Minimum voxel of a sub-block, relative to the chunk
+    pub fn block_min(index: usize) -> UVec3 {
+        let i = index as u32;
+        UVec3::new(
+            i % SUB_BLOCKS_PER_SIDE,
+            i / SUB_BLOCKS_PER_SIDE % SUB_BLOCKS_PER_SIDE,
+            i / (SUB_BLOCKS_PER_SIDE * SUB_BLOCKS_PER_SIDE),
+        ) * SUB_BLOCK_SIDE
+    }
+
+    /// Sub-blocks of the chunk `key` whose mesh depends on the voxels of `extent`.
+    ///
+    /// The cells of a sub-block reach one voxel past its maximum, and the normals and sharp features
+    /// read one more voxel on each side.
+    pub fn reading(key: ChunkKey, extent: &Extent3i) -> Self {
+        let (min, max) = (
+            extent.minimum - key.min_point(),
+            extent.max() - key.min_point(),
+        );
+
+        let mut mask = Self::EMPTY;
+        for index in 0..SUB_BLOCK_COUNT {
+            let block_min = Self::block_min(index).as_ivec3();
+            let (read_min, read_max) = (block_min - 1, block_min + SUB_BLOCK_SIDE as i32 + 2);
+            if min.cmple(read_max).all() && max.cmpge(read_min).all() {
+                mask.0 |= 1 << index;
+            }
+        }
+        mask
+    }
+}
+
+impl BitOr for SubBlockMask {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for SubBlockMask {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+/// Where a chunk entity is in its lifecycle
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+pub enum ChunkState {
+    /// Spawned, waiting for its generation task
+    Queued,
+    Generating,
+    /// In the [`ChunkMap`], not meshed yet
+    Generated,
+    /// A meshing task is running, possibly to remesh the chunk. It isn't remeshed again before
+    /// the result arrives.
+    Meshing,
+    Meshed,
+    /// Removed from the [`ChunkMap`], the entity is despawned
+    Unloading,
+}
+
+impl ChunkState {
+    pub const ALL: [Self; 6] = [
+        Self::Queued,
+        Self::Generating,
+        Self::Generated,
+        Self::Meshing,
+        Self::Meshed,
+        Self::Unloading,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Queued => "Queued",
+            Self::Generating => "Generating",
+            Self::Generated => "Generated",
+            Self::Meshing => "Meshing",
+            Self::Meshed => "Meshed",
+            Self::Unloading => "Unloading",
+        }
+    }
+
+    /// A chunk can be unloaded at any time, and remeshed once its mesh has arrived
+    pub fn can_transition_to(self, next: Self) -> bool {
+        use ChunkState::*;
+
+        matches!(
+            (self, next),
+            (Queued, Generating)
+                | (Generating, Generated)
+                | (Generated | Meshed, Meshing)
+                | (Meshing, Meshed)
+        ) || (self != Unloading && next == Unloading)
+    }
+
+    pub fn transition(&mut self, next: Self) -> Result<(), InvalidTransition> {
+        if !self.can_transition_to(next) {
+            return Err(InvalidTransition {
+                from: *self,
+                to: next,
+            });
+        }
+        *self = next;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidTransition {
+    pub from: ChunkState,
+    pub to: ChunkState,
 }
 
-#[derive(Resource, Default, Deref, DerefMut)]
-pub struct DirtyChunks(HashSet<ChunkKey>);
+impl fmt::Display for InvalidTransition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "invalid chunk state transition from {} to {}",
+            self.from.name(),
+            self.to.name()
+        )
+    }
+}
+
+impl std::error::Error for InvalidTransition {}
+
+/// Sent when the data of a chunk is inserted in the [`ChunkMap`]
+#[derive(Event, Debug, Clone, Copy)]
+pub struct ChunkGenerated {
+    pub key: ChunkKey,
+    pub entity: Entity,
+}
+
+/// Sent when the mesh of a chunk is inserted, including remeshes and chunks without surface
+#[derive(Event, Debug, Clone, Copy)]
+pub struct ChunkMeshed {
+    pub key: ChunkKey,
+    pub entity: Entity,
+}
+
+/// Sent when a chunk is removed from the [`ChunkMap`], its entity is despawned at the same time
+#[derive(Event, Debug, Clone, Copy)]
+pub struct ChunkUnloaded {
+    pub key: ChunkKey,
+}
 
 pub fn chunks_in_extent(extent: &Extent3i) -> impl Iterator<Item = ChunkKey> {
     let range_min = extent.minimum >> CHUNK_SHAPE_LOG2;
@@ -135,3 +636,16 @@ pub fn chunks_in_extent(extent: &Extent3i) -> impl Iterator<Item = ChunkKey> {
         .iter3()
         .map(ChunkKey::from)
 }
+
+/// The 7 chunks on the positive side of `key` whose voxels are copied into its padding
+pub fn padding_neighbors(key: ChunkKey) -> impl Iterator<Item = ChunkKey> {
+    chunks_in_extent(&key.extent().with_shape(PADDED_CHUNK_SHAPE)).filter(move |&k| k != key)
+}
+
+/// The 7 chunks on the negative side of `key` whose padding contains its voxels
+pub fn padding_dependents(key: ChunkKey) -> impl Iterator<Item = ChunkKey> {
+    Extent3i::from_min_and_max(key.0 - 1, key.0)
+        .iter3()
+        .map(ChunkKey::from)
+        .filter(move |&k| k != key)
+}
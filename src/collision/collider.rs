@@ -0,0 +1,115 @@
+use bevy::{
+    prelude::*,
+    render::mesh::{Indices, VertexAttributeValues},
+};
+
+/// Triangle mesh collider of a chunk, built by the meshing task from the same (possibly simplified)
+/// triangles as the rendered mesh, in the local space of the chunk entity.
+///
+/// It is plain data so that it can be handed to a physics engine, and it can be ray cast directly.
+#[derive(Component, Debug, Clone)]
+pub struct ChunkCollider {
+    pub vertices: Vec<Vec3>,
+    pub triangles: Vec<[u32; 3]>,
+    pub aabb_min: Vec3,
+    pub aabb_max: Vec3,
+}
+
+impl ChunkCollider {
+    pub fn from_mesh(mesh: &Mesh) -> Option<Self> {
+        let Some(VertexAttributeValues::Float32x3(positions)) =
+            mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+        else {
+            return None;
+        };
+
+        let vertices: Vec<Vec3> = positions.iter().map(|&p| Vec3::from(p)).collect();
+        let triangles = match mesh.indices() {
+            Some(Indices::U32(indices)) => indices
+                .chunks_exact(3)
+                .map(|t| [t[0], t[1], t[2]])
+                .collect(),
+            Some(Indices::U16(indices)) => indices
+                .chunks_exact(3)
+                .map(|t| [t[0] as u32, t[1] as u32, t[2] as u32])
+                .collect(),
+            // Flat shaded meshes don't have indices
+            None => (0..vertices.len() as u32)
+                .collect::<Vec<_>>()
+                .chunks_exact(3)
+                .map(|t| [t[0], t[1], t[2]])
+                .collect(),
+        };
+
+        let (aabb_min, aabb_max) = vertices.iter().fold(
+            (Vec3::splat(f32::MAX), Vec3::splat(f32::MIN)),
+            |(min, max), &v| (min.min(v), max.max(v)),
+        );
+
+        Some(Self {
+            vertices,
+            triangles,
+            aabb_min,
+            aabb_max,
+        })
+    }
+
+    /// Distance along the ray to the closest triangle hit, `direction` must be normalized
+    pub fn ray_cast(&self, origin: Vec3, direction: Vec3, max_distance: f32) -> Option<f32> {
+        if !ray_hits_aabb(
+            origin,
+            direction,
+            max_distance,
+            self.aabb_min,
+            self.aabb_max,
+        ) {
+            return None;
+        }
+
+        self.triangles
+            .iter()
+            .filter_map(|t| {
+                let [a, b, c] = t.map(|i| self.vertices[i as usize]);
+                ray_triangle(origin, direction, a, b, c)
+            })
+            .filter(|&d| d <= max_distance)
+            .min_by(f32::total_cmp)
+    }
+}
+
+/// Slab test
+fn ray_hits_aabb(origin: Vec3, direction: Vec3, max_distance: f32, min: Vec3, max: Vec3) -> bool {
+    let inverse = direction.recip();
+    let t0 = (min - origin) * inverse;
+    let t1 = (max - origin) * inverse;
+    let near = t0.min(t1).max_element().max(0.0);
+    let far = t0.max(t1).min_element().min(max_distance);
+    near <= far
+}
+
+/// Möller–Trumbore intersection, both faces are hit
+fn ray_triangle(origin: Vec3, direction: Vec3, a: Vec3, b: Vec3, c: Vec3) -> Option<f32> {
+    let ab = b - a;
+    let ac = c - a;
+    let p = direction.cross(ac);
+    let determinant = ab.dot(p);
+    if determinant.abs() < f32::EPSILON {
+        return None;
+    }
+
+    let inverse_determinant = 1.0 / determinant;
+    let ao = origin - a;
+    let u = ao.dot(p) * inverse_determinant;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+
+    let q = ao.cross(ab);
+    let v = direction.dot(q) * inverse_determinant;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+
+    let t = ac.dot(q) * inverse_determinant;
+    (t >= 0.0).then_some(t)
+}
@@ -0,0 +1,155 @@
+mod collider;
+
+use bevy::prelude::*;
+
+use crate::{chunk_map::ChunkMap, meshing::MeshingSettings, LEVEL_OF_DETAIL};
+
+pub use collider::ChunkCollider;
+
+/// Builds a collider for every meshed chunk and moves the character controllers
+pub struct CollisionPlugin;
+
+impl Plugin for CollisionPlugin {
+    fn build(&self, app: &mut App) {
+        app.world
+            .get_resource_or_insert_with(MeshingSettings::default)
+            .colliders = true;
+
+        app.register_type::<CharacterController>()
+            .add_systems(Update, move_character_controllers);
+    }
+}
+
+/// Capsule moved by its velocity and pushed out of the terrain by sampling the `ChunkMap`, so it
+/// doesn't need a physics engine. The axis of the capsule is the local Y axis of the transform.
+///
+/// The stored distances are clamped to one voxel, so the radius must stay below one voxel for the
+/// penetration to be measurable.
+#[derive(Component, Debug, Clone, Reflect)]
+pub struct CharacterController {
+    pub radius: f32,
+    /// Distance from the center to the center of each spherical cap
+    pub half_height: f32,
+    pub velocity: Vec3,
+    pub gravity: Vec3,
+    /// Whether the capsule rests on a surface facing against the gravity
+    pub grounded: bool,
+}
+
+impl Default for CharacterController {
+    fn default() -> Self {
+        Self {
+            radius: 0.4 * LEVEL_OF_DETAIL,
+            half_height: 0.5 * LEVEL_OF_DETAIL,
+            velocity: Vec3::ZERO,
+            gravity: Vec3::NEG_Y * 9.81,
+            grounded: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct CapsuleContact {
+    /// Center of the capsule once pushed out of the terrain
+    pub position: Vec3,
+    /// Normal of the deepest contact, `None` if the capsule wasn't touching anything
+    pub normal: Option<Vec3>,
+}
+
+impl CapsuleContact {
+    /// Whether the capsule rests on a surface facing against `gravity`, steep slopes don't count
+    pub fn is_grounded(&self, gravity: Vec3) -> bool {
+        self.normal.map_or(false, |normal| {
+            normal.dot(-gravity.normalize_or_zero()) > 0.7
+        })
+    }
+}
+
+/// Number of spheres sampled along the axis of the capsule
+const CAPSULE_SAMPLES: usize = 5;
+const MAX_ITERATIONS: usize = 4;
+const MAX_SUBSTEPS: usize = 64;
+
+/// Pushes a capsule out of the terrain. Returns `None` if it overlaps chunks that aren't loaded,
+/// in which case the caller shouldn't move it.
+pub fn resolve_capsule(
+    chunk_map: &ChunkMap,
+    center: Vec3,
+    axis: Vec3,
+    radius: f32,
+    half_height: f32,
+) -> Option<CapsuleContact> {
+    let mut position = center;
+    let mut normal = None;
+
+    for _ in 0..MAX_ITERATIONS {
+        let mut deepest: Option<(f32, Vec3)> = None;
+
+        for i in 0..CAPSULE_SAMPLES {
+            let t = i as f32 / (CAPSULE_SAMPLES - 1) as f32 * 2.0 - 1.0;
+            let p = (position + axis * half_height * t) / LEVEL_OF_DETAIL;
+
+            let distance = chunk_map.sample_distance(p)? * LEVEL_OF_DETAIL;
+            let penetration = radius - distance;
+
+            if penetration > deepest.map_or(0.0, |(d, _)| d) {
+                let gradient = chunk_map.sample_gradient(p)?.normalize_or_zero();
+                deepest = Some((penetration, gradient));
+            }
+        }
+
+        let Some((penetration, contact_normal)) = deepest else {
+            break;
+        };
+
+        position += contact_normal * penetration;
+        normal = Some(contact_normal);
+    }
+
+    Some(CapsuleContact { position, normal })
+}
+
+pub fn move_character_controllers(
+    time: Res<Time>,
+    chunk_map: Res<ChunkMap>,
+    mut controllers: Query<(&mut Transform, &mut CharacterController)>,
+) {
+    // Avoid tunneling through the terrain after a long frame
+    let dt = time.delta_seconds().min(1.0 / 20.0);
+
+    for (mut transform, mut controller) in controllers.iter_mut() {
+        let gravity = controller.gravity;
+        controller.velocity += gravity * dt;
+
+        // The distance field is only known within a voxel of the surface, so the capsule must not
+        // move by more than its radius at once or it could skip over it
+        let max_step = 0.5 * controller.radius;
+        let steps =
+            ((controller.velocity.length() * dt / max_step).ceil() as usize).clamp(1, MAX_SUBSTEPS);
+        let step_dt = dt / steps as f32;
+
+        controller.grounded = false;
+
+        for _ in 0..steps {
+            let target = transform.translation + controller.velocity * step_dt;
+            let Some(contact) = resolve_capsule(
+                &chunk_map,
+                target,
+                transform.up(),
+                controller.radius,
+                controller.half_height,
+            ) else {
+                break;
+            };
+
+            transform.translation = contact.position;
+            controller.grounded |= contact.is_grounded(gravity);
+
+            if let Some(normal) = contact.normal {
+                // Remove the part of the velocity going into the surface
+                let into_surface = controller.velocity.dot(normal).min(0.0);
+                controller.velocity -= normal * into_surface;
+            }
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use bevy::prelude::*;
+use fast_surface_nets::ndshape::ConstShape;
+
+use crate::chunk::{Chunk, ChunkShape, Face, CHUNK_SIDE, CHUNK_SIZE};
+
+/// Faces touched by the voxel at `p` in a chunk, a mask of bits indexed by [`Face`]
+fn faces_touched_by(p: [u32; 3]) -> u8 {
+    let mut faces = 0;
+    for (axis, &c) in p.iter().enumerate() {
+        if c == 0 {
+            faces |= 1 << (2 * axis);
+        }
+        if c == CHUNK_SIDE - 1 {
+            faces |= 1 << (2 * axis + 1);
+        }
+    }
+    faces
+}
+
+/// Which faces of a chunk are connected by the open voxels inside it, a face is connected to
+/// itself if it has open voxels. The chunks of the world are culled by walking through these
+/// connections from the camera.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FaceConnectivity(u64);
+
+impl FaceConnectivity {
+    /// Chunk without any open voxel
+    pub const NONE: Self = Self(0);
+    /// Empty chunk, or chunk whose connectivity isn't known yet
+    pub const ALL: Self = Self((1 << 36) - 1);
+
+    pub fn connects(self, a: Face, b: Face) -> bool {
+        self.0 & (1 << (a as usize * 6 + b as usize)) != 0
+    }
+
+    pub fn is_open(self, face: Face) -> bool {
+        self.connects(face, face)
+    }
+
+    /// Connects every pair of `faces`, a mask of bits indexed by [`Face`]
+    fn connect_all(&mut self, faces: u8) {
+        for a in 0..6 {
+            if faces & (1 << a) != 0 {
+                for b in 0..6 {
+                    if faces & (1 << b) != 0 {
+                        self.0 |= 1 << (a * 6 + b);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Flood fills the open voxels from the faces of the chunk, the voxels on the surface count as
+    /// open so that nothing visible is culled
+    pub fn from_chunk(chunk: &Chunk) -> Self {
+        let is_open = |i: usize| chunk.sdf[i].0 >= 0;
+
+        let mut connectivity = Self::NONE;
+        let mut visited = [0u64; CHUNK_SIZE / 64];
+        let mut stack = Vec::new();
+
+        for seed in 0..CHUNK_SIZE as u32 {
+            let p = ChunkShape::delinearize(seed);
+            if faces_touched_by(p) == 0 {
+                continue;
+            }
+            let seed = seed as usize;
+            if visited[seed / 64] & (1 << (seed % 64)) != 0 || !is_open(seed) {
+                continue;
+            }
+
+            let mut faces = 0;
+            visited[seed / 64] |= 1 << (seed % 64);
+            stack.push(seed as u32);
+
+            while let Some(i) = stack.pop() {
+                let p = ChunkShape::delinearize(i);
+                faces |= faces_touched_by(p);
+
+                for axis in 0..3 {
+                    for step in [-1i32, 1] {
+                        let c = p[axis] as i32 + step;
+                        if !(0..CHUNK_SIDE as i32).contains(&c) {
+                            continue;
+                        }
+                        let mut q = p;
+                        q[axis] = c as u32;
+                        let j = ChunkShape::linearize(q) as usize;
+                        if visited[j / 64] & (1 << (j % 64)) == 0 && is_open(j) {
+                            visited[j / 64] |= 1 << (j % 64);
+                            stack.push(j as u32);
+                        }
+                    }
+                }
+            }
+
+            connectivity.connect_all(faces);
+            if connectivity == Self::ALL {
+                break;
+            }
+        }
+
+        connectivity
+    }
+}
@@ -0,0 +1,151 @@
+mod connectivity;
+
+use std::{collections::VecDeque, time::Duration};
+
+use bevy::{prelude::*, render::view::VisibilitySystems, utils::HashSet};
+
+use crate::{
+    chunk::ChunkKey,
+    chunk_map::{ChunkMeshed, ChunkUnloaded, CurrentChunks},
+    priority::ChunkViewer,
+    LEVEL_OF_DETAIL,
+};
+
+pub use crate::chunk::Face;
+pub use connectivity::FaceConnectivity;
+
+/// Hides the chunks that can't be seen from the camera's chunk through the open voxels of the
+/// chunks in between, without querying the GPU
+pub struct CullingPlugin;
+
+impl Plugin for CullingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CullingSettings>()
+            .register_type::<CullingSettings>()
+            .init_resource::<CulledChunks>()
+            .add_systems(
+                PostUpdate,
+                cull_chunks.before(VisibilitySystems::VisibilityPropagate),
+            );
+    }
+}
+
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+pub struct CullingSettings {
+    pub enabled: bool,
+}
+
+impl Default for CullingSettings {
+    fn default() -> Self {
+        Self { enabled: true }
+    }
+}
+
+/// Number of chunks hidden by the last culling pass
+#[derive(Resource, Debug, Default, Deref)]
+pub struct CulledChunks(pub usize);
+
+/// Chunks visible from `start`, walking through the faces connected inside each chunk and never
+/// turning back towards the start. The walk stays between `min` and `max`, the chunks outside are
+/// considered empty like the ones missing from `connectivity`.
+pub fn visible_chunks(
+    start: ChunkKey,
+    min: IVec3,
+    max: IVec3,
+    connectivity: impl Fn(ChunkKey) -> Option<FaceConnectivity>,
+) -> HashSet<ChunkKey> {
+    let start = ChunkKey(start.clamp(min, max));
+    let mut visible = HashSet::default();
+    // Chunk, face it was entered through, and directions walked so far
+    let mut queue = VecDeque::from([(start, None::<Face>, 0u8)]);
+    visible.insert(start);
+
+    while let Some((key, entered, directions)) = queue.pop_front() {
+        let chunk = connectivity(key).unwrap_or(FaceConnectivity::ALL);
+
+        for face in Face::ALL {
+            if directions & (1 << face.opposite() as usize) != 0 {
+                continue;
+            }
+            if entered.is_some_and(|entered| !chunk.connects(entered, face)) {
+                continue;
+            }
+
+            let next = ChunkKey(key.0 + face.offset());
+            if next.cmplt(min).any() || next.cmpgt(max).any() || !visible.insert(next) {
+                continue;
+            }
+            queue.push_back((next, Some(face.opposite()), directions | 1 << face as usize));
+        }
+    }
+
+    visible
+}
+
+/// Shortest time between two culling passes caused by chunks being meshed or unloaded, a pass
+/// walks every visible chunk
+const CULLING_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Default)]
+struct CullingState {
+    last_start: Option<ChunkKey>,
+    last_pass: Duration,
+    /// Chunks were meshed or unloaded since the last pass
+    pending: bool,
+}
+
+/// Runs right away when the camera enters another chunk or the settings change, the chunks meshed
+/// or unloaded meanwhile are only taken into account every [`CULLING_INTERVAL`]
+fn cull_chunks(
+    settings: Res<CullingSettings>,
+    viewer: Res<ChunkViewer>,
+    time: Res<Time>,
+    mut culled: ResMut<CulledChunks>,
+    mut meshed: EventReader<ChunkMeshed>,
+    mut unloaded: EventReader<ChunkUnloaded>,
+    current_chunks: Res<CurrentChunks>,
+    connectivities: Query<&FaceConnectivity>,
+    mut visibilities: Query<(&ChunkKey, &mut Visibility)>,
+    mut state: Local<CullingState>,
+) {
+    let start = ChunkKey::from_voxel((viewer.position / LEVEL_OF_DETAIL).floor().as_ivec3());
+    state.pending |= meshed.iter().count() + unloaded.iter().count() > 0;
+
+    let moved = state.last_start != Some(start);
+    let due = state.pending && time.elapsed() >= state.last_pass + CULLING_INTERVAL;
+    if !moved && !due && !settings.is_changed() {
+        return;
+    }
+    state.last_start = Some(start);
+    state.last_pass = time.elapsed();
+    state.pending = false;
+
+    if !settings.enabled {
+        for (_, mut visibility) in visibilities.iter_mut() {
+            visibility.set_if_neq(Visibility::Inherited);
+        }
+        culled.0 = 0;
+        return;
+    }
+
+    let Some((min, max)) = current_chunks.bounds() else {
+        return;
+    };
+
+    // The camera may be outside of the loaded chunks, the walk starts from the empty layer
+    // around them
+    let visible = visible_chunks(start, min - IVec3::ONE, max + IVec3::ONE, |key| {
+        let entity = current_chunks.get_entity(key)?;
+        connectivities.get(entity).ok().copied()
+    });
+
+    culled.0 = 0;
+    for (key, mut visibility) in visibilities.iter_mut() {
+        if visible.contains(key) {
+            visibility.set_if_neq(Visibility::Inherited);
+        } else {
+            visibility.set_if_neq(Visibility::Hidden);
+            culled.0 += 1;
+        }
+    }
+}
@@ -6,24 +6,42 @@ use bevy_egui::{egui, EguiContexts};
 
 use crate::{
     chunk::ChunkKey,
-    chunk_map::{ChunkCommand, ChunkCommandQueue, ChunkMap, DirtyChunks},
-    generation::GenerationResults,
-    meshing::MeshingResults,
+    chunk_map::{ChunkCommand, ChunkCommandQueue, ChunkMap, ChunkState, DirtyChunks},
+    culling::{CulledChunks, CullingSettings},
+    editing::BrushEvent,
+    export::{ExportFormat, ExportRequest, ExportSource},
+    generation::{CsgOperation, GenerationResults, MeshSdf, MeshStamp},
+    meshing::{
+        ChunkColoring, ChunkMeshStats, MesherKind, MeshingResults, MeshingSettings, NormalsMode,
+    },
+    priority::ChunkViewer,
+    scheduling::{
+        Scheduling, VoxelTaskStats, VoxelThreadingConfig, GENERATION_LATENCY, MESHING_LATENCY,
+    },
+    LEVEL_OF_DETAIL,
 };
 
+/// Voxels between the camera and the meshes stamped with the brush
+const BRUSH_DISTANCE: f32 = 24.0;
+
 pub struct DebugPlugin;
 
 impl Plugin for DebugPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(FrameTimeDiagnosticsPlugin)
             .init_resource::<DebugUiState>()
-            .add_systems(Update, ui_debug);
+            .add_systems(Update, (ui_debug, ui_export, ui_brush));
     }
 }
 
 #[derive(Resource, Default)]
 struct DebugUiState {
     chunk_key: (i32, i32, i32),
+    export_format: ExportFormat,
+    export_source: ExportSource,
+    export_weld: bool,
+    brush_path: String,
+    brush_carve: bool,
 }
 
 fn ui_debug(
@@ -36,6 +54,12 @@ fn ui_debug(
     chunk_map: Res<ChunkMap>,
     gen_results: Res<GenerationResults>,
     meshing_results: Res<MeshingResults>,
+    mut meshing_settings: ResMut<MeshingSettings>,
+    mesh_stats: Query<&ChunkMeshStats>,
+    chunk_states: Query<&ChunkState>,
+    task_stats: Res<VoxelTaskStats>,
+    mut threading_config: ResMut<VoxelThreadingConfig>,
+    (mut culling_settings, culled_chunks): (ResMut<CullingSettings>, Res<CulledChunks>),
 ) {
     egui::Window::new("Debug").show(contexts.ctx_mut(), |ui| {
         ui.label(format!(
@@ -57,21 +81,219 @@ fn ui_debug(
             ("Chunk map entries", chunk_map.storage.len()),
             ("Generation results", gen_results.len()),
             ("Meshing results", meshing_results.len()),
+            ("Culled chunks", **culled_chunks),
         ] {
             ui.label(format!("{k}: {v}"));
         }
 
         ui.separator();
 
+        let mut state_counts = [0; ChunkState::ALL.len()];
+        for &state in chunk_states.iter() {
+            state_counts[state as usize] += 1;
+        }
+        egui::Grid::new("chunk_states").show(ui, |ui| {
+            for (state, count) in ChunkState::ALL.into_iter().zip(state_counts) {
+                ui.label(state.name());
+                ui.label(count.to_string());
+                ui.end_row();
+            }
+        });
+
+        ui.separator();
+
+        egui::Grid::new("task_stats").show(ui, |ui| {
+            for label in ["Stage", "Queued", "Tasks", "Budget", "Latency"] {
+                ui.label(label);
+            }
+            ui.end_row();
+            for (name, stage, latency) in [
+                ("Generation", &task_stats.generation, GENERATION_LATENCY),
+                ("Meshing", &task_stats.meshing, MESHING_LATENCY),
+            ] {
+                let latency = diagnostics
+                    .get(latency)
+                    .and_then(|d| d.average())
+                    .unwrap_or_default();
+                ui.label(name);
+                ui.label(stage.queued.to_string());
+                ui.label(stage.in_flight.to_string());
+                ui.label(stage.budget.to_string());
+                ui.label(format!("{latency:.1} ms"));
+                ui.end_row();
+            }
+        });
+        // The task pools are rebuilt for the new mode
+        let mut scheduling = threading_config.scheduling;
+        egui::ComboBox::from_label("Scheduling")
+            .selected_text(scheduling.name())
+            .show_ui(ui, |ui| {
+                for mode in Scheduling::ALL {
+                    ui.selectable_value(&mut scheduling, mode, mode.name());
+                }
+            });
+        if scheduling != threading_config.scheduling {
+            threading_config.scheduling = scheduling;
+        }
+
+        let mut culling = *culling_settings;
+        ui.checkbox(&mut culling.enabled, "Occlusion culling");
+        if culling != *culling_settings {
+            *culling_settings = culling;
+        }
+
+        ui.separator();
+
+        let mut settings = *meshing_settings;
+        egui::ComboBox::from_label("Mesher")
+            .selected_text(settings.mesher.name())
+            .show_ui(ui, |ui| {
+                for kind in MesherKind::ALL {
+                    ui.selectable_value(&mut settings.mesher, kind, kind.name());
+                }
+            });
+        ui.add_enabled(
+            settings.mesher == MesherKind::SurfaceNets,
+            egui::Checkbox::new(&mut settings.sharp_features, "Sharp features"),
+        );
+        egui::ComboBox::from_label("Normals")
+            .selected_text(settings.normals.name())
+            .show_ui(ui, |ui| {
+                for mode in NormalsMode::ALL {
+                    ui.selectable_value(&mut settings.normals, mode, mode.name());
+                }
+            });
+        egui::ComboBox::from_label("Colors")
+            .selected_text(settings.coloring.name())
+            .show_ui(ui, |ui| {
+                for coloring in ChunkColoring::ALL {
+                    ui.selectable_value(&mut settings.coloring, coloring, coloring.name());
+                }
+            });
+        ui.checkbox(&mut settings.colliders, "Build colliders");
+        ui.checkbox(&mut settings.simplify, "Simplify meshes");
+        ui.add_enabled_ui(settings.simplify, |ui| {
+            ui.add(
+                egui::Slider::new(&mut settings.simplification.max_error, 0.0..=0.5)
+                    .logarithmic(true)
+                    .text("Max error"),
+            );
+            ui.add(
+                egui::Slider::new(&mut settings.simplification.target_ratio, 0.0..=1.0)
+                    .text("Target triangle ratio"),
+            );
+            ui.add(
+                egui::Slider::new(&mut settings.simplification.near_distance, 0.0..=512.0)
+                    .text("Full detail distance"),
+            );
+            ui.add(
+                egui::Slider::new(&mut settings.simplification.far_distance, 0.0..=1024.0)
+                    .text("Target ratio distance"),
+            );
+        });
+        meshing_settings.set_if_neq(settings);
+
+        let (generated_triangles, final_triangles) =
+            mesh_stats
+                .iter()
+                .fold((0, 0), |(generated, simplified), s| {
+                    (
+                        generated + s.generated_triangles,
+                        simplified + s.final_triangles,
+                    )
+                });
+        ui.label(format!(
+            "Triangles: {final_triangles} ({generated_triangles} before simplification)"
+        ));
+
+        ui.separator();
+
         ui.label("Chunk key:");
         ui.horizontal(|ui| {
             ui.add(egui::DragValue::new(&mut ui_state.chunk_key.0));
             ui.add(egui::DragValue::new(&mut ui_state.chunk_key.1));
             ui.add(egui::DragValue::new(&mut ui_state.chunk_key.2));
         });
-        if ui.button("Add chunk").clicked() {
+        ui.horizontal(|ui| {
             let chunk_key = ChunkKey(IVec3::from(ui_state.chunk_key));
-            chunk_command_queue.push(ChunkCommand::Create(chunk_key));
+            if ui.button("Add chunk").clicked() {
+                chunk_command_queue.push(ChunkCommand::Create(chunk_key));
+            }
+            if ui.button("Remove chunk").clicked() {
+                chunk_command_queue.push(ChunkCommand::Delete(chunk_key));
+            }
+        });
+    });
+}
+
+fn ui_export(
+    mut contexts: EguiContexts,
+    mut ui_state: ResMut<DebugUiState>,
+    mut export_requests: EventWriter<ExportRequest>,
+) {
+    egui::Window::new("Export").show(contexts.ctx_mut(), |ui| {
+        egui::ComboBox::from_label("Format")
+            .selected_text(ui_state.export_format.name())
+            .show_ui(ui, |ui| {
+                for format in ExportFormat::ALL {
+                    ui.selectable_value(&mut ui_state.export_format, format, format.name());
+                }
+            });
+
+        ui.horizontal(|ui| {
+            ui.radio_value(
+                &mut ui_state.export_source,
+                ExportSource::Entities,
+                "Loaded meshes",
+            );
+            ui.radio_value(
+                &mut ui_state.export_source,
+                ExportSource::ChunkMap,
+                "Remesh chunk map",
+            );
+        });
+
+        ui.checkbox(&mut ui_state.export_weld, "Weld chunk seams");
+
+        if ui.button("Export").clicked() {
+            export_requests.send(ExportRequest {
+                path: format!("exports/terrain.{}", ui_state.export_format.extension()).into(),
+                format: ui_state.export_format,
+                source: ui_state.export_source,
+                weld: ui_state.export_weld,
+            });
+        }
+    });
+}
+
+fn ui_brush(
+    mut contexts: EguiContexts,
+    mut ui_state: ResMut<DebugUiState>,
+    viewer: Res<ChunkViewer>,
+    mut brushes: EventWriter<BrushEvent>,
+) {
+    egui::Window::new("Brush").show(contexts.ctx_mut(), |ui| {
+        ui.horizontal(|ui| {
+            ui.label("Mesh (OBJ or GLB):");
+            ui.text_edit_singleline(&mut ui_state.brush_path);
+        });
+        ui.checkbox(&mut ui_state.brush_carve, "Carve");
+
+        if ui.button("Stamp in front of the camera").clicked() {
+            // Two samples per voxel of the world
+            match MeshSdf::load(ui_state.brush_path.as_ref(), LEVEL_OF_DETAIL / 2.0) {
+                Ok(sdf) => brushes.send(BrushEvent(MeshStamp {
+                    sdf: sdf.into(),
+                    translation: viewer.position + viewer.forward * BRUSH_DISTANCE,
+                    scale: 1.0,
+                    operation: if ui_state.brush_carve {
+                        CsgOperation::Subtraction
+                    } else {
+                        CsgOperation::Union
+                    },
+                })),
+                Err(e) => error!("Failed to load the brush {}: {e}", ui_state.brush_path),
+            }
         }
     });
 }
@@ -0,0 +1,74 @@
+use bevy::prelude::*;
+
+use crate::{
+    chunk::{ChunkKey, Extent3i, Sd8},
+    chunk_map::{chunks_in_extent, ChunkMap, DirtyChunks, SubBlockMask},
+    generation::MeshStamp,
+    LEVEL_OF_DETAIL,
+};
+
+/// Edits the loaded chunks with the shapes sent as [`BrushEvent`]
+pub struct EditingPlugin;
+
+impl Plugin for EditingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<BrushEvent>()
+            .add_systems(Update, apply_brushes.run_if(on_event::<BrushEvent>()));
+    }
+}
+
+/// Stamps a shape into the loaded chunks and remeshes them
+#[derive(Event, Debug, Clone)]
+pub struct BrushEvent(pub MeshStamp);
+
+/// Combines the stamp with the voxels of the loaded chunks it overlaps, and returns the extent of
+/// the voxels it may have changed
+pub fn apply_brush(chunk_map: &ChunkMap, stamp: &MeshStamp) -> Extent3i {
+    let (min, max) = stamp.bounds();
+    let extent = Extent3i::from_min_and_max(
+        (min / LEVEL_OF_DETAIL).floor().as_ivec3(),
+        (max / LEVEL_OF_DETAIL).ceil().as_ivec3(),
+    );
+
+    for key in chunks_in_extent(&extent) {
+        chunk_map.storage.edit(&key, |chunk| {
+            for p in extent.intersection(&key.extent()).iter3() {
+                let offset = p - key.min_point();
+                let d = f32::from(chunk.get_voxel(offset)) * LEVEL_OF_DETAIL;
+                let edited = stamp.apply(d, p.as_vec3() * LEVEL_OF_DETAIL);
+                chunk.set_voxel(offset, Sd8::from(edited / LEVEL_OF_DETAIL));
+            }
+        });
+    }
+
+    extent
+}
+
+/// Chunks whose padded neighborhood overlaps the voxels of `extent`, they must be remeshed when
+/// those voxels change
+pub fn chunks_to_remesh(extent: &Extent3i) -> impl Iterator<Item = ChunkKey> {
+    // The padding reaches two voxels past the maximum of the chunk
+    let padded = Extent3i::from_min_and_max(extent.minimum - 2, extent.max());
+    chunks_in_extent(&padded)
+}
+
+/// Marks dirty the sub-blocks of the loaded chunks that read the voxels of `extent`
+pub fn mark_edited(chunk_map: &ChunkMap, extent: &Extent3i, dirty_chunks: &mut DirtyChunks) {
+    for key in chunks_to_remesh(extent).filter(|k| chunk_map.storage.contains_key(k)) {
+        let blocks = SubBlockMask::reading(key, extent);
+        if !blocks.is_empty() {
+            dirty_chunks.insert_blocks(key, blocks);
+        }
+    }
+}
+
+fn apply_brushes(
+    mut brushes: EventReader<BrushEvent>,
+    chunk_map: Res<ChunkMap>,
+    mut dirty_chunks: ResMut<DirtyChunks>,
+) {
+    for BrushEvent(stamp) in brushes.iter() {
+        let extent = apply_brush(&chunk_map, stamp);
+        mark_edited(&chunk_map, &extent, &mut dirty_chunks);
+    }
+}
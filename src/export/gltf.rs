@@ -0,0 +1,77 @@
+use std::io::{self, Write};
+
+use bevy::prelude::*;
+
+use super::MergedMesh;
+
+const GLB_MAGIC: &[u8; 4] = b"glTF";
+const GLB_VERSION: u32 = 2;
+const CHUNK_TYPE_JSON: &[u8; 4] = b"JSON";
+const CHUNK_TYPE_BIN: &[u8; 4] = b"BIN\0";
+
+const COMPONENT_TYPE_UNSIGNED_INT: u32 = 5125;
+const COMPONENT_TYPE_FLOAT: u32 = 5126;
+const TARGET_ARRAY_BUFFER: u32 = 34962;
+const TARGET_ELEMENT_ARRAY_BUFFER: u32 = 34963;
+
+/// Writes a binary glTF 2.0 (`.glb`) file with a single mesh, which can't be empty since glTF
+/// doesn't allow empty buffers and accessors
+pub fn write(mesh: &MergedMesh, writer: &mut impl Write) -> io::Result<()> {
+    let vertex_count = mesh.positions.len();
+    let index_count = mesh.indices.len();
+    if index_count == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "glTF can't store an empty mesh",
+        ));
+    }
+
+    let mut bin = Vec::with_capacity(vertex_count * 24 + index_count * 4);
+    mesh.positions
+        .iter()
+        .chain(mesh.normals.iter())
+        .flatten()
+        .for_each(|v| bin.extend_from_slice(&v.to_le_bytes()));
+    mesh.indices
+        .iter()
+        .for_each(|i| bin.extend_from_slice(&i.to_le_bytes()));
+
+    // The POSITION accessor must declare its bounds
+    let (min, max) = mesh.positions.iter().fold(
+        (Vec3::splat(f32::MAX), Vec3::splat(f32::MIN)),
+        |(min, max), &p| (min.min(Vec3::from(p)), max.max(Vec3::from(p))),
+    );
+
+    let attribute_length = vertex_count * 12;
+    let json = format!(
+        r#"{{"asset":{{"version":"2.0","generator":"surface-nets-experiment"}},"scene":0,"scenes":[{{"nodes":[0]}}],"nodes":[{{"mesh":0,"name":"Terrain"}}],"meshes":[{{"primitives":[{{"attributes":{{"POSITION":0,"NORMAL":1}},"indices":2,"mode":4}}]}}],"buffers":[{{"byteLength":{buffer_length}}}],"bufferViews":[{{"buffer":0,"byteOffset":0,"byteLength":{attribute_length},"target":{TARGET_ARRAY_BUFFER}}},{{"buffer":0,"byteOffset":{attribute_length},"byteLength":{attribute_length},"target":{TARGET_ARRAY_BUFFER}}},{{"buffer":0,"byteOffset":{indices_offset},"byteLength":{indices_length},"target":{TARGET_ELEMENT_ARRAY_BUFFER}}}],"accessors":[{{"bufferView":0,"componentType":{COMPONENT_TYPE_FLOAT},"count":{vertex_count},"type":"VEC3","min":[{},{},{}],"max":[{},{},{}]}},{{"bufferView":1,"componentType":{COMPONENT_TYPE_FLOAT},"count":{vertex_count},"type":"VEC3"}},{{"bufferView":2,"componentType":{COMPONENT_TYPE_UNSIGNED_INT},"count":{index_count},"type":"SCALAR"}}]}}"#,
+        min.x,
+        min.y,
+        min.z,
+        max.x,
+        max.y,
+        max.z,
+        buffer_length = bin.len(),
+        indices_offset = 2 * attribute_length,
+        indices_length = index_count * 4,
+    );
+
+    // Chunks must be 4-byte aligned, JSON is padded with spaces and binary data with zeros
+    let mut json = json.into_bytes();
+    json.resize((json.len() + 3) & !3, b' ');
+    bin.resize((bin.len() + 3) & !3, 0);
+
+    let total_length = 12 + 8 + json.len() + 8 + bin.len();
+
+    writer.write_all(GLB_MAGIC)?;
+    writer.write_all(&GLB_VERSION.to_le_bytes())?;
+    writer.write_all(&(total_length as u32).to_le_bytes())?;
+
+    for (chunk_type, data) in [(CHUNK_TYPE_JSON, &json), (CHUNK_TYPE_BIN, &bin)] {
+        writer.write_all(&(data.len() as u32).to_le_bytes())?;
+        writer.write_all(chunk_type)?;
+        writer.write_all(data)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,270 @@
+mod gltf;
+mod obj;
+mod ply;
+mod stl;
+
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use bevy::{prelude::*, render::mesh::VertexAttributeValues, tasks::IoTaskPool, utils::HashMap};
+
+use crate::{
+    chunk::{ChunkKey, Extent3i},
+    chunk_map::ChunkMap,
+    generation::{ChunkGenerator, WorldGenerator},
+    meshing::{mesh_chunk, MeshingSettings},
+    LEVEL_OF_DETAIL,
+};
+
+pub struct ExportPlugin;
+
+impl Plugin for ExportPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ExportRequest>()
+            .add_systems(Update, handle_export_requests);
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    #[default]
+    Obj,
+    Ply,
+    Stl,
+    Gltf,
+}
+
+impl ExportFormat {
+    pub const ALL: [Self; 4] = [Self::Obj, Self::Ply, Self::Stl, Self::Gltf];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Obj => "OBJ",
+            Self::Ply => "PLY (binary)",
+            Self::Stl => "STL (binary)",
+            Self::Gltf => "glTF 2.0 (binary)",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Obj => "obj",
+            Self::Ply => "ply",
+            Self::Stl => "stl",
+            Self::Gltf => "glb",
+        }
+    }
+
+    pub fn write(self, mesh: &MergedMesh, writer: &mut impl Write) -> io::Result<()> {
+        match self {
+            Self::Obj => obj::write(mesh, writer),
+            Self::Ply => ply::write(mesh, writer),
+            Self::Stl => stl::write(mesh, writer),
+            Self::Gltf => gltf::write(mesh, writer),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ExportSource {
+    /// The meshes of the chunk entities, as they are rendered
+    #[default]
+    Entities,
+    /// Meshes every chunk of the `ChunkMap` again with the current `MeshingSettings`
+    ChunkMap,
+}
+
+#[derive(Event, Debug, Clone)]
+pub struct ExportRequest {
+    pub path: PathBuf,
+    pub format: ExportFormat,
+    pub source: ExportSource,
+    /// Merges the vertices duplicated along the seams between chunks
+    pub weld: bool,
+}
+
+/// Tolerance used to weld the seams, chunk meshes are built from the same samples so their shared
+/// vertices only differ by floating point errors
+pub const WELD_TOLERANCE: f32 = 1e-3;
+
+/// Chunk meshes merged into a single triangle list in world space
+#[derive(Debug, Default)]
+pub struct MergedMesh {
+    pub positions: Vec<[f32; 3]>,
+    pub normals: Vec<[f32; 3]>,
+    pub indices: Vec<u32>,
+}
+
+impl MergedMesh {
+    /// Appends a chunk mesh, `translation` and `scale` being the ones of the chunk's transform
+    pub fn append(&mut self, mesh: &Mesh, translation: Vec3, scale: f32) {
+        let (
+            Some(VertexAttributeValues::Float32x3(positions)),
+            Some(VertexAttributeValues::Float32x3(normals)),
+        ) = (
+            mesh.attribute(Mesh::ATTRIBUTE_POSITION),
+            mesh.attribute(Mesh::ATTRIBUTE_NORMAL),
+        )
+        else {
+            return;
+        };
+
+        let offset = self.positions.len() as u32;
+
+        self.positions.extend(
+            positions
+                .iter()
+                .map(|&p| (Vec3::from(p) * scale + translation).to_array()),
+        );
+        self.normals.extend_from_slice(normals);
+
+        // Flat shaded meshes don't have indices
+        match mesh.indices() {
+            Some(indices) => self
+                .indices
+                .extend(indices.iter().map(|i| offset + i as u32)),
+            None => self.indices.extend(offset..offset + positions.len() as u32),
+        }
+    }
+
+    /// Merges the vertices closer than `tolerance` to each other, averaging their normals
+    pub fn weld(&mut self, tolerance: f32) {
+        // Vertices within the tolerance can fall on either side of a cell boundary, the
+        // neighboring cells are searched too
+        let mut cells = HashMap::<IVec3, Vec<u32>>::default();
+        let mut remap = Vec::with_capacity(self.positions.len());
+        let mut positions = Vec::new();
+        let mut normals: Vec<Vec3> = Vec::new();
+
+        for (&p, &n) in self.positions.iter().zip(self.normals.iter()) {
+            let position = Vec3::from(p);
+            let cell = (position / tolerance).floor().as_ivec3();
+
+            let neighbor = Extent3i::from_min_and_shape(cell - IVec3::ONE, IVec3::splat(3))
+                .iter3()
+                .filter_map(|c| cells.get(&c))
+                .flatten()
+                .copied()
+                .find(|&i| Vec3::from(positions[i as usize]).distance(position) <= tolerance);
+            let index = neighbor.unwrap_or_else(|| {
+                positions.push(p);
+                normals.push(Vec3::ZERO);
+                let index = positions.len() as u32 - 1;
+                cells.entry(cell).or_default().push(index);
+                index
+            });
+
+            normals[index as usize] += Vec3::from(n);
+            remap.push(index);
+        }
+
+        self.indices
+            .iter_mut()
+            .for_each(|i| *i = remap[*i as usize]);
+        // Drop the triangles collapsed by the welding
+        self.indices = self
+            .indices
+            .chunks_exact(3)
+            .filter(|t| t[0] != t[1] && t[1] != t[2] && t[2] != t[0])
+            .flatten()
+            .copied()
+            .collect();
+        self.positions = positions;
+        self.normals = normals
+            .into_iter()
+            .map(|n| n.normalize_or_zero().to_array())
+            .collect();
+    }
+
+    pub fn triangle_count(&self) -> usize {
+        self.indices.len() / 3
+    }
+
+    fn triangles(&self) -> impl Iterator<Item = [Vec3; 3]> + '_ {
+        self.indices
+            .chunks_exact(3)
+            .map(|t| [t[0], t[1], t[2]].map(|i| Vec3::from(self.positions[i as usize])))
+    }
+}
+
+/// Meshes every chunk of the map, independently of what is currently rendered
+pub fn mesh_chunk_map(
+    chunk_map: &ChunkMap,
+    generator: &dyn ChunkGenerator,
+    settings: &MeshingSettings,
+) -> MergedMesh {
+    let mesher = settings.mesher();
+    let mut merged = MergedMesh::default();
+
+    for key in chunk_map.storage.keys() {
+        let padded_sdf = chunk_map.copy_chunk_neighborhood(key);
+        if let Some((mesh, _)) =
+            mesh_chunk(key, &padded_sdf, mesher.as_ref(), generator, settings, 0)
+        {
+            merged.append(
+                &mesh,
+                key.min_point().as_vec3() * LEVEL_OF_DETAIL,
+                LEVEL_OF_DETAIL,
+            );
+        }
+    }
+
+    merged
+}
+
+pub fn write_to_file(mesh: &MergedMesh, format: ExportFormat, path: &Path) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let mut writer = BufWriter::new(File::create(path)?);
+    format.write(mesh, &mut writer)?;
+    writer.flush()
+}
+
+fn handle_export_requests(
+    mut export_requests: EventReader<ExportRequest>,
+    chunks: Query<(&Handle<Mesh>, &Transform), With<ChunkKey>>,
+    meshes: Res<Assets<Mesh>>,
+    chunk_map: Res<ChunkMap>,
+    meshing_settings: Res<MeshingSettings>,
+    generator: Res<WorldGenerator>,
+) {
+    for request in export_requests.iter() {
+        let mut merged = match request.source {
+            ExportSource::Entities => {
+                let mut merged = MergedMesh::default();
+                for (handle, transform) in chunks.iter() {
+                    if let Some(mesh) = meshes.get(handle) {
+                        merged.append(mesh, transform.translation, transform.scale.x);
+                    }
+                }
+                merged
+            }
+            ExportSource::ChunkMap => {
+                mesh_chunk_map(&chunk_map, generator.as_ref(), &meshing_settings)
+            }
+        };
+
+        let request = request.clone();
+        IoTaskPool::get()
+            .spawn(async move {
+                if request.weld {
+                    merged.weld(WELD_TOLERANCE);
+                }
+
+                match write_to_file(&merged, request.format, &request.path) {
+                    Ok(()) => info!(
+                        "Exported {} triangles to {}",
+                        merged.triangle_count(),
+                        request.path.display()
+                    ),
+                    Err(e) => error!("Failed to export to {}: {e}", request.path.display()),
+                }
+            })
+            .detach();
+    }
+}
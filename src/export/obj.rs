@@ -0,0 +1,23 @@
+use std::io::{self, Write};
+
+use super::MergedMesh;
+
+pub fn write(mesh: &MergedMesh, writer: &mut impl Write) -> io::Result<()> {
+    writeln!(writer, "# surface-nets-experiment terrain")?;
+
+    for [x, y, z] in &mesh.positions {
+        writeln!(writer, "v {x} {y} {z}")?;
+    }
+
+    for [x, y, z] in &mesh.normals {
+        writeln!(writer, "vn {x} {y} {z}")?;
+    }
+
+    // OBJ indices start at 1
+    for t in mesh.indices.chunks_exact(3) {
+        let [a, b, c] = [t[0] + 1, t[1] + 1, t[2] + 1];
+        writeln!(writer, "f {a}//{a} {b}//{b} {c}//{c}")?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,39 @@
+use std::io::{self, Write};
+
+use super::MergedMesh;
+
+pub fn write(mesh: &MergedMesh, writer: &mut impl Write) -> io::Result<()> {
+    write!(
+        writer,
+        "ply\n\
+         format binary_little_endian 1.0\n\
+         comment surface-nets-experiment terrain\n\
+         element vertex {}\n\
+         property float x\n\
+         property float y\n\
+         property float z\n\
+         property float nx\n\
+         property float ny\n\
+         property float nz\n\
+         element face {}\n\
+         property list uchar uint vertex_indices\n\
+         end_header\n",
+        mesh.positions.len(),
+        mesh.triangle_count(),
+    )?;
+
+    for (position, normal) in mesh.positions.iter().zip(mesh.normals.iter()) {
+        for v in position.iter().chain(normal.iter()) {
+            writer.write_all(&v.to_le_bytes())?;
+        }
+    }
+
+    for t in mesh.indices.chunks_exact(3) {
+        writer.write_all(&[3])?;
+        for i in t {
+            writer.write_all(&i.to_le_bytes())?;
+        }
+    }
+
+    Ok(())
+}
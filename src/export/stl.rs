@@ -0,0 +1,25 @@
+use std::io::{self, Write};
+
+use super::MergedMesh;
+
+pub fn write(mesh: &MergedMesh, writer: &mut impl Write) -> io::Result<()> {
+    let mut header = [0u8; 80];
+    let title = b"surface-nets-experiment terrain";
+    header[..title.len()].copy_from_slice(title);
+    writer.write_all(&header)?;
+
+    writer.write_all(&(mesh.triangle_count() as u32).to_le_bytes())?;
+
+    // STL has no shared vertices, every triangle stores its face normal and its three corners
+    for [a, b, c] in mesh.triangles() {
+        let normal = (b - a).cross(c - a).normalize_or_zero();
+        for v in [normal, a, b, c] {
+            for component in v.to_array() {
+                writer.write_all(&component.to_le_bytes())?;
+            }
+        }
+        writer.write_all(&0u16.to_le_bytes())?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,210 @@
+use bevy::prelude::*;
+use bracket_noise::prelude::FractalType;
+
+use super::{noise::NoiseLayer, WorldSeed};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+pub enum Biome {
+    Desert,
+    Grassland,
+    Forest,
+    Jungle,
+    Tundra,
+    Snow,
+}
+
+impl Biome {
+    pub const COUNT: usize = 6;
+    pub const ALL: [Self; Self::COUNT] = [
+        Self::Desert,
+        Self::Grassland,
+        Self::Forest,
+        Self::Jungle,
+        Self::Tundra,
+        Self::Snow,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Desert => "Desert",
+            Self::Grassland => "Grassland",
+            Self::Forest => "Forest",
+            Self::Jungle => "Jungle",
+            Self::Tundra => "Tundra",
+            Self::Snow => "Snow",
+        }
+    }
+
+    /// Temperature and moisture where the biome is the most present, both in -1..1
+    fn climate(self) -> Vec2 {
+        match self {
+            Self::Desert => Vec2::new(0.7, -0.6),
+            Self::Grassland => Vec2::new(0.2, -0.2),
+            Self::Forest => Vec2::new(0.1, 0.5),
+            Self::Jungle => Vec2::new(0.8, 0.6),
+            Self::Tundra => Vec2::new(-0.5, -0.1),
+            Self::Snow => Vec2::new(-0.9, 0.3),
+        }
+    }
+
+    pub fn params(self) -> BiomeParams {
+        let (height_offset, height_scale, frequency, octaves, roughness, amplitude) = match self {
+            // Long smooth dunes
+            Self::Desert => (2.0, 0.4, 0.006, 2, 0.3, 5.0),
+            Self::Grassland => (0.0, 0.6, 0.01, 3, 0.5, 4.0),
+            Self::Forest => (0.0, 1.0, 0.015, 4, 0.5, 5.0),
+            // Steep and broken hills
+            Self::Jungle => (-2.0, 0.8, 0.025, 5, 0.65, 7.0),
+            Self::Tundra => (0.0, 0.7, 0.008, 3, 0.4, 3.0),
+            // Jagged peaks
+            Self::Snow => (4.0, 1.2, 0.02, 6, 0.7, 9.0),
+        };
+
+        BiomeParams {
+            height_offset,
+            height_scale,
+            frequency,
+            octaves,
+            roughness,
+            amplitude,
+        }
+    }
+
+    pub fn color(self) -> Color {
+        match self {
+            Self::Desert => Color::rgb(0.86, 0.76, 0.52),
+            Self::Grassland => Color::rgb(0.55, 0.68, 0.32),
+            Self::Forest => Color::rgb(0.22, 0.42, 0.2),
+            Self::Jungle => Color::rgb(0.12, 0.36, 0.12),
+            Self::Tundra => Color::rgb(0.52, 0.5, 0.42),
+            Self::Snow => Color::rgb(0.92, 0.94, 0.96),
+        }
+    }
+}
+
+/// Generator parameters that vary from one biome to another
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BiomeParams {
+    /// Added to the height of the terrain
+    pub height_offset: f32,
+    /// Multiplies the height of the terrain
+    pub height_scale: f32,
+    /// Frequency of the detail noise of the biome, added to the terrain
+    pub frequency: f32,
+    pub octaves: i32,
+    /// Gain between the octaves of the detail noise
+    pub roughness: f32,
+    /// Height of the detail noise
+    pub amplitude: f32,
+}
+
+/// Subtracted from the weights of the biomes when blending their heights, so that only the detail
+/// noises of the biomes around a point are sampled without creating steps in the terrain
+const MIN_WEIGHT: f32 = 0.01;
+
+/// Picks the biomes from a temperature, which decreases toward the poles, and a moisture noise
+pub struct BiomeMap {
+    temperature: NoiseLayer,
+    moisture: NoiseLayer,
+    /// Detail noise of each biome of `Biome::ALL`, built from its parameters
+    details: Vec<NoiseLayer>,
+    /// Width of the transitions between biomes, in climate units
+    pub blend_width: f32,
+}
+
+impl Default for BiomeMap {
+    fn default() -> Self {
+        Self::new(WorldSeed::default())
+    }
+}
+
+impl BiomeMap {
+    pub fn new(seed: WorldSeed) -> Self {
+        Self {
+            temperature: NoiseLayer::fractal(seed.derive(30), 0.003, FractalType::FBM, 3)
+                .scale(0.4),
+            moisture: NoiseLayer::fractal(seed.derive(31), 0.002, FractalType::FBM, 3)
+                .warp(NoiseLayer::simplex(seed.derive(32), 0.004), 30.0),
+            details: Biome::ALL
+                .into_iter()
+                .enumerate()
+                .map(|(i, biome)| {
+                    let params = biome.params();
+                    NoiseLayer::fractal(
+                        seed.derive(33 + i as u64),
+                        params.frequency,
+                        FractalType::FBM,
+                        params.octaves,
+                    )
+                    .with_gain(params.roughness)
+                    .scale(params.amplitude)
+                })
+                .collect(),
+            blend_width: 0.15,
+        }
+    }
+
+    /// Temperature and moisture at `p`, only the direction of `p` from the center of the planet
+    /// matters so the biomes are the same at every depth
+    pub fn climate(&self, p: Vec3) -> Vec2 {
+        let direction = p.normalize_or_zero();
+        let latitude = direction.y.abs();
+
+        let temperature = 1.0 - 2.0 * latitude + self.temperature.sample(p);
+        let moisture = self.moisture.sample(p);
+
+        Vec2::new(temperature, moisture).clamp(Vec2::NEG_ONE, Vec2::ONE)
+    }
+
+    /// Weight of each biome of `Biome::ALL` at `p`, they sum to one
+    pub fn weights(&self, p: Vec3) -> [f32; Biome::COUNT] {
+        let climate = self.climate(p);
+        let distances = Biome::ALL.map(|b| climate.distance_squared(b.climate()));
+        let nearest = distances.into_iter().fold(f32::INFINITY, f32::min);
+
+        // Relative to the nearest biome so the closest one always has a weight of one
+        let weights =
+            distances.map(|d| (-(d - nearest) / (self.blend_width * self.blend_width)).exp());
+        let total: f32 = weights.iter().sum();
+
+        weights.map(|w| w / total)
+    }
+
+    pub fn dominant(&self, p: Vec3) -> Biome {
+        let weights = self.weights(p);
+
+        Biome::ALL
+            .into_iter()
+            .zip(weights)
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(biome, _)| biome)
+            .unwrap()
+    }
+
+    /// Height of the terrain at `p`, from the height of the shared terrain layer there. Each biome
+    /// scales and offsets it and adds its own detail noise, blended by their weights.
+    pub fn height(&self, p: Vec3, terrain: f32) -> f32 {
+        let (height, total) = Biome::ALL
+            .into_iter()
+            .zip(self.weights(p).map(|w| w - MIN_WEIGHT))
+            .filter(|&(_, w)| w > 0.0)
+            .fold((0.0, 0.0), |(height, total), (biome, w)| {
+                let params = biome.params();
+                let detail = self.details[biome as usize].sample(p);
+                let biome_height = params.height_offset + params.height_scale * terrain + detail;
+                (height + biome_height * w, total + w)
+            });
+
+        height / total
+    }
+
+    pub fn color(&self, p: Vec3) -> Color {
+        let rgba = Biome::ALL
+            .into_iter()
+            .zip(self.weights(p))
+            .map(|(biome, w)| Vec4::from(biome.color().as_linear_rgba_f32()) * w)
+            .sum::<Vec4>();
+
+        Color::rgba_linear(rgba.x, rgba.y, rgba.z, rgba.w)
+    }
+}
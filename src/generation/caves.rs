@@ -0,0 +1,76 @@
+use bevy::prelude::*;
+use bracket_noise::prelude::{FastNoise, NoiseType};
+
+use super::splitmix64;
+
+/// Upper bound of how fast the simplex noise changes per unit of its input, used to turn noise
+/// values into approximate distances
+const NOISE_LIPSCHITZ: f32 = 2.5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaveKind {
+    /// Large chambers where the noise rises above the threshold
+    Cheese,
+    /// Tunnels along the intersection of the zero sets of two noises, the threshold is their radius
+    /// in noise units
+    Worm,
+}
+
+/// 3D noise volume carved out of the terrain. The caves only open between `min_depth` and
+/// `max_depth` below the surface and shrink at the rate of one voxel per voxel outside of it, so
+/// the field stays close to a distance.
+pub struct CaveLayer {
+    pub kind: CaveKind,
+    pub frequency: f32,
+    pub threshold: f32,
+    pub min_depth: f32,
+    pub max_depth: f32,
+    noises: [FastNoise; 2],
+}
+
+impl CaveLayer {
+    pub fn new(kind: CaveKind, seed: u64, frequency: f32, threshold: f32) -> Self {
+        let noise = |seed| {
+            let mut n = FastNoise::new();
+            n.set_seed(seed);
+            n.set_noise_type(NoiseType::Simplex);
+            n
+        };
+
+        Self {
+            kind,
+            frequency,
+            threshold,
+            min_depth: 0.0,
+            max_depth: f32::INFINITY,
+            noises: [noise(seed), noise(splitmix64(seed))],
+        }
+    }
+
+    pub fn with_depth(mut self, min_depth: f32, max_depth: f32) -> Self {
+        self.min_depth = min_depth;
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Approximate signed distance to the cave volume, negative inside it. `depth` is the distance
+    /// below the surface of the base shape.
+    pub fn signed_distance(&self, p: Vec3, depth: f32) -> f32 {
+        let [x, y, z] = (p * self.frequency).to_array();
+
+        let field = match self.kind {
+            CaveKind::Cheese => self.threshold - self.noises[0].get_noise3d(x, y, z),
+            CaveKind::Worm => {
+                let a = self.noises[0].get_noise3d(x, y, z);
+                let b = self.noises[1].get_noise3d(x, y, z);
+                // The length of two noises changes at most sqrt(2) times faster than one
+                (Vec2::new(a, b).length() - self.threshold) / std::f32::consts::SQRT_2
+            }
+        };
+
+        // Depth is a distance too, so growing the field by it keeps the result close to one
+        let outside_band = (self.min_depth - depth).max(0.0) + (depth - self.max_depth).max(0.0);
+
+        field / (NOISE_LIPSCHITZ * self.frequency) + outside_band
+    }
+}
@@ -0,0 +1,324 @@
+use bevy::prelude::*;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use super::WorldSeed;
+
+/// Normal, U and V axes of the six faces of the cube
+const FACES: [(Vec3, Vec3, Vec3); 6] = [
+    (Vec3::X, Vec3::NEG_Z, Vec3::Y),
+    (Vec3::NEG_X, Vec3::Z, Vec3::Y),
+    (Vec3::Y, Vec3::X, Vec3::NEG_Z),
+    (Vec3::NEG_Y, Vec3::X, Vec3::Z),
+    (Vec3::Z, Vec3::X, Vec3::Y),
+    (Vec3::NEG_Z, Vec3::NEG_X, Vec3::Y),
+];
+
+/// Cells over which the erosion fades out at the edges of the faces, the faces are eroded
+/// separately so this keeps the heights continuous across them
+const EDGE_FADE_CELLS: f32 = 4.0;
+
+/// Heights stored on the six faces of a cube projected onto the sphere, each face being a square
+/// grid of `resolution` cells
+#[derive(Debug, Clone)]
+pub struct CubeSphereHeightmap {
+    resolution: usize,
+    heights: Vec<f32>,
+}
+
+impl CubeSphereHeightmap {
+    /// Fills the heightmap with `height` evaluated at the direction of the center of each cell
+    pub fn from_fn(resolution: usize, height: impl Fn(Vec3) -> f32) -> Self {
+        let mut heights = Vec::with_capacity(6 * resolution * resolution);
+
+        for face in 0..6 {
+            for y in 0..resolution {
+                for x in 0..resolution {
+                    let cell = Vec2::new(x as f32, y as f32) + 0.5;
+                    heights.push(height(Self::direction(face, cell / resolution as f32)));
+                }
+            }
+        }
+
+        Self {
+            resolution,
+            heights,
+        }
+    }
+
+    /// Heightmap from the heights of the six faces in the order of [`FACES`], each face being
+    /// stored row by row
+    pub fn from_faces(resolution: usize, heights: Vec<f32>) -> Self {
+        assert_eq!(heights.len(), 6 * resolution * resolution);
+
+        Self {
+            resolution,
+            heights,
+        }
+    }
+
+    pub fn resolution(&self) -> usize {
+        self.resolution
+    }
+
+    /// Direction from the center of the planet through `uv` (in 0..1) on a face
+    fn direction(face: usize, uv: Vec2) -> Vec3 {
+        let (normal, u, v) = FACES[face];
+        let uv = uv * 2.0 - 1.0;
+        (normal + u * uv.x + v * uv.y).normalize()
+    }
+
+    /// Face and position in cells, where the centers of the cells are at integer coordinates
+    fn face_position(&self, direction: Vec3) -> (usize, Vec2) {
+        let (face, &(normal, u, v)) = FACES
+            .iter()
+            .enumerate()
+            .max_by(|(_, a), (_, b)| direction.dot(a.0).total_cmp(&direction.dot(b.0)))
+            .unwrap();
+
+        let on_face = direction / direction.dot(normal);
+        let uv = Vec2::new(on_face.dot(u), on_face.dot(v)) * 0.5 + 0.5;
+        (face, uv * self.resolution as f32 - 0.5)
+    }
+
+    fn index(&self, face: usize, x: usize, y: usize) -> usize {
+        (face * self.resolution + y) * self.resolution + x
+    }
+
+    /// Bilinear height on a face, clamped to its edges
+    fn sample_face(&self, face: usize, position: Vec2) -> f32 {
+        let max = (self.resolution - 1) as f32;
+        let position = position.clamp(Vec2::ZERO, Vec2::splat(max));
+        let cell = position.floor().min(Vec2::splat((max - 1.0).max(0.0)));
+        let t = position - cell;
+        let (x, y) = (cell.x as usize, cell.y as usize);
+        let (x1, y1) = (
+            (x + 1).min(self.resolution - 1),
+            (y + 1).min(self.resolution - 1),
+        );
+
+        let h00 = self.heights[self.index(face, x, y)];
+        let h10 = self.heights[self.index(face, x1, y)];
+        let h01 = self.heights[self.index(face, x, y1)];
+        let h11 = self.heights[self.index(face, x1, y1)];
+
+        let bottom = h00 + (h10 - h00) * t.x;
+        let top = h01 + (h11 - h01) * t.x;
+        bottom + (top - bottom) * t.y
+    }
+
+    pub fn sample(&self, direction: Vec3) -> f32 {
+        let (face, position) = self.face_position(direction);
+        self.sample_face(face, position)
+    }
+}
+
+/// Parameters of the erosion, the same settings and seed always give the same heights
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ErosionSettings {
+    pub seed: u64,
+    pub resolution: usize,
+    /// Number of water droplets simulated over the whole planet
+    pub droplets: usize,
+    pub droplet_lifetime: usize,
+    /// How much a droplet keeps its direction instead of following the slope, in 0..1
+    pub inertia: f32,
+    /// Sediment a droplet can carry per unit of speed, water and slope
+    pub sediment_capacity: f32,
+    pub min_sediment_capacity: f32,
+    pub erode_speed: f32,
+    pub deposit_speed: f32,
+    pub evaporate_speed: f32,
+    pub gravity: f32,
+    pub thermal_iterations: usize,
+    /// Height difference between neighboring cells above which the material slides down
+    pub talus: f32,
+}
+
+impl Default for ErosionSettings {
+    fn default() -> Self {
+        Self {
+            seed: WorldSeed::default().derive(40),
+            resolution: 128,
+            droplets: 60_000,
+            droplet_lifetime: 30,
+            inertia: 0.05,
+            sediment_capacity: 4.0,
+            min_sediment_capacity: 0.01,
+            erode_speed: 0.3,
+            deposit_speed: 0.3,
+            evaporate_speed: 0.01,
+            gravity: 4.0,
+            thermal_iterations: 20,
+            talus: 3.0,
+        }
+    }
+}
+
+/// Erodes the heights given by `height` and returns the difference between the eroded and the
+/// original heights, which can be added to a finer heightfield than the simulated one
+pub fn erode(settings: &ErosionSettings, height: impl Fn(Vec3) -> f32) -> CubeSphereHeightmap {
+    let original = CubeSphereHeightmap::from_fn(settings.resolution, height);
+    let mut eroded = original.clone();
+
+    let mut rng = StdRng::seed_from_u64(settings.seed);
+    for _ in 0..settings.droplets {
+        let face = rng.gen_range(0..6);
+        let max = (settings.resolution - 1) as f32;
+        let position = Vec2::new(rng.gen_range(0.0..max), rng.gen_range(0.0..max));
+        simulate_droplet(&mut eroded, settings, face, position);
+    }
+
+    for _ in 0..settings.thermal_iterations {
+        thermal_erosion(&mut eroded, settings.talus);
+    }
+
+    let resolution = settings.resolution;
+    let mut delta = eroded;
+    for face in 0..6 {
+        for y in 0..resolution {
+            for x in 0..resolution {
+                let i = delta.index(face, x, y);
+                let edge_distance = x.min(y).min(resolution - 1 - x).min(resolution - 1 - y);
+                let fade = (edge_distance as f32 / EDGE_FADE_CELLS).min(1.0);
+                delta.heights[i] = (delta.heights[i] - original.heights[i]) * fade;
+            }
+        }
+    }
+
+    delta
+}
+
+fn height_and_gradient(
+    heightmap: &CubeSphereHeightmap,
+    face: usize,
+    position: Vec2,
+) -> (f32, Vec2) {
+    let cell = position.floor();
+    let t = position - cell;
+    let (x, y) = (cell.x as usize, cell.y as usize);
+
+    let h00 = heightmap.heights[heightmap.index(face, x, y)];
+    let h10 = heightmap.heights[heightmap.index(face, x + 1, y)];
+    let h01 = heightmap.heights[heightmap.index(face, x, y + 1)];
+    let h11 = heightmap.heights[heightmap.index(face, x + 1, y + 1)];
+
+    let gradient = Vec2::new(
+        (h10 - h00) * (1.0 - t.y) + (h11 - h01) * t.y,
+        (h01 - h00) * (1.0 - t.x) + (h11 - h10) * t.x,
+    );
+    let height = h00 * (1.0 - t.x) * (1.0 - t.y)
+        + h10 * t.x * (1.0 - t.y)
+        + h01 * (1.0 - t.x) * t.y
+        + h11 * t.x * t.y;
+
+    (height, gradient)
+}
+
+/// Adds `amount` to the four cells around `position`, weighted by their proximity
+fn splat(heightmap: &mut CubeSphereHeightmap, face: usize, position: Vec2, amount: f32) {
+    let cell = position.floor();
+    let t = position - cell;
+    let (x, y) = (cell.x as usize, cell.y as usize);
+
+    for (dx, dy, weight) in [
+        (0, 0, (1.0 - t.x) * (1.0 - t.y)),
+        (1, 0, t.x * (1.0 - t.y)),
+        (0, 1, (1.0 - t.x) * t.y),
+        (1, 1, t.x * t.y),
+    ] {
+        let i = heightmap.index(face, x + dx, y + dy);
+        heightmap.heights[i] += amount * weight;
+    }
+}
+
+/// Moves a droplet down the slope, eroding where it's fast and depositing where it slows down.
+/// The droplet stops at the edge of its face.
+fn simulate_droplet(
+    heightmap: &mut CubeSphereHeightmap,
+    settings: &ErosionSettings,
+    face: usize,
+    mut position: Vec2,
+) {
+    let max = (heightmap.resolution - 1) as f32;
+    let mut direction = Vec2::ZERO;
+    let mut speed = 1.0;
+    let mut water = 1.0;
+    let mut sediment = 0.0;
+
+    for _ in 0..settings.droplet_lifetime {
+        let (height, gradient) = height_and_gradient(heightmap, face, position);
+
+        direction = direction * settings.inertia - gradient * (1.0 - settings.inertia);
+        let Some(direction_normalized) = direction.try_normalize() else {
+            break;
+        };
+        direction = direction_normalized;
+
+        let new_position = position + direction;
+        if new_position.cmplt(Vec2::ZERO).any() || new_position.cmpge(Vec2::splat(max)).any() {
+            break;
+        }
+
+        let (new_height, _) = height_and_gradient(heightmap, face, new_position);
+        let delta_height = new_height - height;
+
+        let capacity = (-delta_height * speed * water * settings.sediment_capacity)
+            .max(settings.min_sediment_capacity);
+
+        if sediment > capacity || delta_height > 0.0 {
+            // Fill the pit the droplet climbs out of, or drop what it can't carry anymore
+            let amount = if delta_height > 0.0 {
+                delta_height.min(sediment)
+            } else {
+                (sediment - capacity) * settings.deposit_speed
+            };
+            sediment -= amount;
+            splat(heightmap, face, position, amount);
+        } else {
+            // Never dig deeper than the next position to avoid creating pits
+            let amount = ((capacity - sediment) * settings.erode_speed).min(-delta_height);
+            sediment += amount;
+            splat(heightmap, face, position, -amount);
+        }
+
+        speed = (speed * speed - delta_height * settings.gravity)
+            .max(0.0)
+            .sqrt();
+        water *= 1.0 - settings.evaporate_speed;
+        position = new_position;
+    }
+}
+
+/// Slides the material down the slopes steeper than `talus`
+fn thermal_erosion(heightmap: &mut CubeSphereHeightmap, talus: f32) {
+    let resolution = heightmap.resolution;
+    let mut deltas = vec![0.0; heightmap.heights.len()];
+
+    for face in 0..6 {
+        for y in 0..resolution {
+            for x in 0..resolution {
+                let i = heightmap.index(face, x, y);
+                let neighbors = [
+                    (x > 0).then(|| heightmap.index(face, x - 1, y)),
+                    (x + 1 < resolution).then(|| heightmap.index(face, x + 1, y)),
+                    (y > 0).then(|| heightmap.index(face, x, y - 1)),
+                    (y + 1 < resolution).then(|| heightmap.index(face, x, y + 1)),
+                ];
+
+                for n in neighbors.into_iter().flatten() {
+                    let difference = heightmap.heights[i] - heightmap.heights[n];
+                    if difference > talus {
+                        // A quarter since up to four neighbors may take from the same cell
+                        let amount = 0.25 * 0.5 * (difference - talus);
+                        deltas[i] -= amount;
+                        deltas[n] += amount;
+                    }
+                }
+            }
+        }
+    }
+
+    for (height, delta) in heightmap.heights.iter_mut().zip(deltas) {
+        *height += delta;
+    }
+}
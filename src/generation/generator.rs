@@ -1,72 +1,198 @@
+use std::sync::Arc;
+
 use bevy::prelude::*;
-use bracket_noise::prelude::{FastNoise, FractalType, NoiseType};
+use bracket_noise::prelude::FractalType;
 use once_cell::sync::Lazy;
 use tracing::instrument;
 
-use super::sdf;
+use super::{
+    biomes::BiomeMap,
+    caves::{CaveKind, CaveLayer},
+    erosion::{self, CubeSphereHeightmap, ErosionSettings},
+    noise::NoiseLayer,
+    sdf,
+    voxelizer::MeshStamp,
+    WorldSeed,
+};
 use crate::{
     chunk::{Chunk, ChunkKey, Sd8},
     LEVEL_OF_DETAIL,
 };
 
-pub static GENERATOR: Lazy<Generator> = Lazy::new(Default::default);
+/// Generator of the world of the default [`WorldSeed`] without erosion, shared by the tests and
+/// the tools so they don't simulate it
+pub static GENERATOR: Lazy<Arc<Generator>> =
+    Lazy::new(|| Arc::new(Generator::new(WorldSeed::default())));
 
-pub struct Generator {
-    simplex_fractal_rigid_multi: FastNoise,
+/// Generator of the world of `seed` without erosion, reusing [`GENERATOR`] for the default seed.
+/// The erosion is opted into with [`Generator::for_world`].
+pub fn world_generator(seed: WorldSeed) -> Arc<Generator> {
+    if seed == WorldSeed::default() {
+        GENERATOR.clone()
+    } else {
+        Arc::new(Generator::new(seed))
+    }
 }
 
-impl Default for Generator {
-    fn default() -> Self {
-        Generator {
-            simplex_fractal_rigid_multi: {
-                let mut n = FastNoise::new();
-                n.set_seed(43210);
-                n.set_noise_type(NoiseType::SimplexFractal);
-                n.set_fractal_octaves(6);
-                n.set_fractal_type(FractalType::RigidMulti);
-                n
-            },
-        }
+/// Radius of the planet before the terrain is added
+pub const SPHERE_RADIUS: f32 = 260.0;
+
+/// Source of the signed distances of the world, either procedural like [`Generator`] or imported
+pub trait ChunkGenerator: Send + Sync {
+    /// Signed distance at `p` in world units, negative inside the matter
+    fn signed_distance(&self, p: Vec3) -> f32;
+
+    /// Gradient of the signed distance at `p`, approximated with central differences since the
+    /// sources usually have no analytic derivative
+    fn gradient(&self, p: Vec3) -> Vec3 {
+        const H: f32 = 0.05;
+
+        Vec3::new(
+            self.signed_distance(p + Vec3::X * H) - self.signed_distance(p - Vec3::X * H),
+            self.signed_distance(p + Vec3::Y * H) - self.signed_distance(p - Vec3::Y * H),
+            self.signed_distance(p + Vec3::Z * H) - self.signed_distance(p - Vec3::Z * H),
+        ) / (2.0 * H)
+    }
+
+    /// Biomes of the world, `None` if the generator doesn't have any
+    fn biomes(&self) -> Option<&BiomeMap> {
+        None
+    }
+
+    /// Likely bounds of the signed distance in the box from `min` to `max`, from its center
+    /// assuming the distance doesn't change faster than the position
+    fn distance_bounds(&self, min: Vec3, max: Vec3) -> (f32, f32) {
+        let distance = self.signed_distance((min + max) / 2.0);
+        let half_diagonal = (max - min).length() / 2.0;
+        (distance - half_diagonal, distance + half_diagonal)
     }
-}
 
-impl Generator {
     #[instrument(skip_all, level = "trace")]
-    pub fn generate_chunk(&self, key: ChunkKey) -> Chunk {
+    fn generate_chunk(&self, key: ChunkKey) -> Chunk {
         let chunk_extent = key.extent();
         let mut chunk_data = Chunk::new_empty();
 
         chunk_extent.iter3().for_each(|p| {
             let offset = p - chunk_extent.minimum;
-            let sd = Sd8::from(
-                self.generate_signed_distance(p.as_vec3() * LEVEL_OF_DETAIL)
-                    / LEVEL_OF_DETAIL,
-            );
+            let sd =
+                Sd8::from(self.signed_distance(p.as_vec3() * LEVEL_OF_DETAIL) / LEVEL_OF_DETAIL);
 
             chunk_data.set_voxel(offset, sd);
         });
 
         chunk_data
     }
+}
+
+pub struct Generator {
+    /// Height above the base sphere, sampled on its surface
+    pub terrain: NoiseLayer,
+    /// Scales and offsets the terrain per region, and adds the detail noise of each biome
+    pub biomes: BiomeMap,
+    /// Carved out of the terrain in order
+    pub caves: Vec<CaveLayer>,
+    /// Difference between the eroded and the generated heights, see [`Generator::erode`]
+    pub erosion: Option<CubeSphereHeightmap>,
+    /// Meshes stamped into the terrain after the caves
+    pub props: Vec<MeshStamp>,
+}
+
+impl Default for Generator {
+    fn default() -> Self {
+        Self::new(WorldSeed::default())
+    }
+}
+
+impl Generator {
+    /// Noise layers of the world of `seed`, without erosion. Every layer derives its own seed from
+    /// it so they aren't correlated.
+    pub fn new(seed: WorldSeed) -> Self {
+        Generator {
+            terrain: default_terrain(seed),
+            biomes: BiomeMap::new(seed),
+            caves: vec![
+                CaveLayer::new(CaveKind::Cheese, seed.derive(1), 0.012, 0.6)
+                    .with_depth(24.0, 180.0),
+                CaveLayer::new(CaveKind::Worm, seed.derive(3), 0.008, 0.06).with_depth(2.0, 200.0),
+            ],
+            erosion: None,
+            props: Vec::new(),
+        }
+    }
+
+    /// The complete world of `seed`, with its erosion simulated. It takes a few seconds, the app
+    /// runs it in the background.
+    pub fn for_world(seed: WorldSeed) -> Self {
+        let mut generator = Self::new(seed);
+        generator.erode(&ErosionSettings {
+            seed: seed.derive(40),
+            ..Default::default()
+        });
+        generator
+    }
+
+    /// Runs the erosion simulation on the heights of the terrain, replacing any previous erosion
+    #[instrument(skip_all)]
+    pub fn erode(&mut self, settings: &ErosionSettings) {
+        self.erosion = None;
+        let erosion = erosion::erode(settings, |direction| self.surface_height(direction));
+        self.erosion = Some(erosion);
+    }
 
     fn generate_signed_distance(&self, p: Vec3) -> f32 {
         // infinite_repetition(p, Vec3::splat(80.0), |q| sphere(q, 32.0))
         // infinite_repetition(p, Vec3::splat(256.0), |q| sphere(q, 128.0))
         // sphere(p, 640.0)
 
-        const SPHERE_RADIUS: f32 = 260.0;
-        const NOISE_FREQUENCY: f32 = 0.002;
-        const NOISE_AMPLITUDE: f32 = 60.0;
+        let perturbed_radius = SPHERE_RADIUS + self.surface_height(p.normalize());
 
-        let projected_p = p.normalize() * SPHERE_RADIUS;
-        let noise = self.generate_simplex_fractal_rigid_multi(NOISE_FREQUENCY * projected_p);
-        let perturbed_radius = NOISE_AMPLITUDE * -noise + SPHERE_RADIUS;
+        let base = sdf::sphere(p, perturbed_radius);
 
-        sdf::sphere(p, perturbed_radius)
+        // The stored distances are clamped to one voxel, carving the air can't change them
+        let terrain = if base > LEVEL_OF_DETAIL {
+            base
+        } else {
+            let depth = -base;
+            self.caves.iter().fold(base, |d, cave| {
+                sdf::subtraction(d, cave.signed_distance(p, depth))
+            })
+        };
+
+        self.props.iter().fold(terrain, |d, prop| prop.apply(d, p))
+    }
+
+    /// Height of the surface above the base sphere in `direction`
+    fn surface_height(&self, direction: Vec3) -> f32 {
+        let projected_p = direction * SPHERE_RADIUS;
+        let terrain = self.terrain.sample(projected_p);
+        let eroded = self.erosion.as_ref().map_or(0.0, |e| e.sample(direction));
+
+        self.biomes.height(projected_p, terrain) + eroded
+    }
+}
+
+impl ChunkGenerator for Generator {
+    fn signed_distance(&self, p: Vec3) -> f32 {
+        self.generate_signed_distance(p)
     }
 
-    fn generate_simplex_fractal_rigid_multi(&self, p: Vec3) -> f32 {
-        let [x, y, z] = p.to_array();
-        self.simplex_fractal_rigid_multi.get_noise3d(x, y, z)
+    fn biomes(&self) -> Option<&BiomeMap> {
+        Some(&self.biomes)
     }
 }
+
+/// Continents with mountain ranges where a low frequency mask rises, the biomes add their own
+/// detail on top
+fn default_terrain(seed: WorldSeed) -> NoiseLayer {
+    let continents = NoiseLayer::fractal(seed.derive(10), 0.0015, FractalType::FBM, 4)
+        .warp(NoiseLayer::simplex(seed.derive(11), 0.003), 40.0)
+        .curve([(-1.0, -25.0), (-0.1, -10.0), (0.05, 0.0), (1.0, 15.0)]);
+
+    let mountains = NoiseLayer::fractal(seed.derive(0), 0.002, FractalType::RigidMulti, 6)
+        .warp(NoiseLayer::simplex(seed.derive(13), 0.004), 25.0)
+        .scale(-60.0);
+
+    let mountain_mask = NoiseLayer::simplex(seed.derive(14), 0.001).curve([(0.0, 0.0), (0.4, 1.0)]);
+
+    continents.add(NoiseLayer::Constant(0.0).mask(mountains, mountain_mask))
+}
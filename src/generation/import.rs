@@ -0,0 +1,374 @@
+use std::{
+    fs,
+    io::{self, ErrorKind},
+    path::Path,
+};
+
+use bevy::prelude::*;
+use image::ImageFormat;
+
+use super::{erosion::CubeSphereHeightmap, generator::ChunkGenerator};
+
+/// Largest volume that is loaded, 1 GiB of densities
+const MAX_VOLUME_SAMPLES: u64 = 1 << 28;
+
+fn invalid_data(error: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, error)
+}
+
+/// Grid of heights normalized to 0..1, the first row being the one at the lowest Z (or V on the
+/// faces of a cubemap)
+#[derive(Debug, Clone)]
+pub struct Heightmap {
+    width: usize,
+    height: usize,
+    values: Vec<f32>,
+}
+
+impl Heightmap {
+    /// Loads a grayscale PNG, 16-bit images keep their full precision
+    pub fn load_png(path: &Path) -> io::Result<Self> {
+        Self::from_png(&fs::read(path)?)
+    }
+
+    pub fn from_png(bytes: &[u8]) -> io::Result<Self> {
+        let image = image::load_from_memory_with_format(bytes, ImageFormat::Png)
+            .map_err(invalid_data)?
+            .into_luma16();
+
+        Self::new(
+            image.width() as usize,
+            image.height() as usize,
+            image
+                .into_raw()
+                .into_iter()
+                .map(|v| v as f32 / u16::MAX as f32)
+                .collect(),
+        )
+    }
+
+    /// Loads headerless little-endian 16-bit samples, as exported by most terrain tools
+    pub fn load_raw(path: &Path, width: usize, height: usize) -> io::Result<Self> {
+        Self::from_raw(&fs::read(path)?, width, height)
+    }
+
+    pub fn from_raw(bytes: &[u8], width: usize, height: usize) -> io::Result<Self> {
+        let expected = width.checked_mul(height).and_then(|n| n.checked_mul(2));
+        if expected != Some(bytes.len()) {
+            return Err(invalid_data(format!(
+                "expected {width}x{height} 16-bit samples, got {} bytes",
+                bytes.len()
+            )));
+        }
+
+        Self::new(
+            width,
+            height,
+            bytes
+                .chunks_exact(2)
+                .map(|b| u16::from_le_bytes([b[0], b[1]]) as f32 / u16::MAX as f32)
+                .collect(),
+        )
+    }
+
+    /// Sampling needs at least one pixel, empty images are rejected
+    fn new(width: usize, height: usize, values: Vec<f32>) -> io::Result<Self> {
+        if width == 0 || height == 0 {
+            return Err(invalid_data(format!(
+                "a heightmap can't be empty, got {width}x{height}"
+            )));
+        }
+
+        Ok(Self {
+            width,
+            height,
+            values,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Bilinear value at `p` in pixels, clamped to the edges
+    pub fn sample(&self, p: Vec2) -> f32 {
+        let max = Vec2::new((self.width - 1) as f32, (self.height - 1) as f32);
+        let p = p.clamp(Vec2::ZERO, max);
+        let cell = p.floor().min((max - 1.0).max(Vec2::ZERO));
+        let t = p - cell;
+        let (x, y) = (cell.x as usize, cell.y as usize);
+        let (x1, y1) = ((x + 1).min(self.width - 1), (y + 1).min(self.height - 1));
+
+        let value = |x: usize, y: usize| self.values[y * self.width + x];
+        let bottom = value(x, y) + (value(x1, y) - value(x, y)) * t.x;
+        let top = value(x, y1) + (value(x1, y1) - value(x, y1)) * t.x;
+        bottom + (top - bottom) * t.y
+    }
+}
+
+/// How a [`Heightmap`] is laid onto the world
+#[derive(Debug, Clone)]
+pub enum HeightmapProjection {
+    /// On the XZ plane, one pixel every `pixel_size` voxels starting at the origin
+    Flat {
+        heightmap: Heightmap,
+        pixel_size: f32,
+    },
+    /// The six faces of a cube stacked vertically in the image (+X, -X, +Y, -Y, +Z, -Z) and
+    /// projected onto a sphere of `radius` voxels
+    Cubemap {
+        radius: f32,
+        faces: CubeSphereHeightmap,
+    },
+}
+
+/// Terrain whose height is read from a heightmap instead of noise
+#[derive(Debug, Clone)]
+pub struct HeightmapGenerator {
+    projection: HeightmapProjection,
+    /// Height in voxels of the highest value of the heightmap
+    pub max_height: f32,
+}
+
+impl HeightmapGenerator {
+    pub fn flat(heightmap: Heightmap, pixel_size: f32, max_height: f32) -> Self {
+        Self {
+            projection: HeightmapProjection::Flat {
+                heightmap,
+                pixel_size,
+            },
+            max_height,
+        }
+    }
+
+    pub fn cubemap(heightmap: Heightmap, radius: f32, max_height: f32) -> io::Result<Self> {
+        let resolution = heightmap.width;
+        if heightmap.height != 6 * resolution {
+            return Err(invalid_data(format!(
+                "a cubemap must be 6 square faces stacked vertically, got {}x{}",
+                heightmap.width, heightmap.height
+            )));
+        }
+
+        let faces = CubeSphereHeightmap::from_faces(resolution, heightmap.values);
+
+        Ok(Self {
+            projection: HeightmapProjection::Cubemap { radius, faces },
+            max_height,
+        })
+    }
+}
+
+impl ChunkGenerator for HeightmapGenerator {
+    /// The vertical distance to the surface, which overestimates the distance on steep slopes
+    fn signed_distance(&self, p: Vec3) -> f32 {
+        match &self.projection {
+            HeightmapProjection::Flat {
+                heightmap,
+                pixel_size,
+            } => {
+                let pixel = Vec2::new(p.x, p.z) / *pixel_size;
+                p.y - heightmap.sample(pixel) * self.max_height
+            }
+            HeightmapProjection::Cubemap { radius, faces } => {
+                let direction = p.normalize_or_zero();
+                p.length() - (radius + faces.sample(direction) * self.max_height)
+            }
+        }
+    }
+}
+
+/// Grid of densities in 0..1, where the matter is above `iso`
+#[derive(Debug, Clone)]
+pub struct DensityVolume {
+    size: UVec3,
+    densities: Vec<f32>,
+    pub iso: f32,
+}
+
+impl DensityVolume {
+    /// Loads headerless 8-bit densities, X varying the fastest then Y (up) then Z
+    pub fn load_raw(path: &Path, size: UVec3) -> io::Result<Self> {
+        Self::from_raw(&fs::read(path)?, size)
+    }
+
+    pub fn from_raw(bytes: &[u8], size: UVec3) -> io::Result<Self> {
+        let expected = Self::sample_count(size)?;
+        if bytes.len() != expected {
+            return Err(invalid_data(format!(
+                "expected {}x{}x{} 8-bit samples ({expected} bytes), got {} bytes",
+                size.x,
+                size.y,
+                size.z,
+                bytes.len()
+            )));
+        }
+
+        Ok(Self {
+            size,
+            densities: bytes.iter().map(|&b| b as f32 / 255.0).collect(),
+            iso: 0.5,
+        })
+    }
+
+    /// Loads the first model of a MagicaVoxel file, the filled voxels have a density of one. The
+    /// model is rotated so that the Z up axis of MagicaVoxel becomes the Y axis, MagicaVoxel's Y
+    /// becoming -Z.
+    pub fn load_vox(path: &Path) -> io::Result<Self> {
+        Self::from_vox(&fs::read(path)?)
+    }
+
+    pub fn from_vox(bytes: &[u8]) -> io::Result<Self> {
+        let mut reader = VoxReader { bytes };
+
+        if reader.take(4)? != b"VOX " {
+            return Err(invalid_data("not a MagicaVoxel file"));
+        }
+        reader.read_u32()?;
+
+        let mut volume: Option<Self> = None;
+        let mut filled = false;
+
+        while !reader.bytes.is_empty() {
+            // The children of a chunk follow its content, so they're read like the top level chunks
+            let id = reader.take(4)?;
+            let content_size = reader.read_u32()? as usize;
+            let _children_size = reader.read_u32()?;
+            let mut content = VoxReader {
+                bytes: reader.take(content_size)?,
+            };
+
+            match id {
+                b"SIZE" if volume.is_none() => {
+                    let x = content.read_u32()?;
+                    let y = content.read_u32()?;
+                    let z = content.read_u32()?;
+                    let size = UVec3::new(x, z, y);
+                    volume = Some(Self {
+                        size,
+                        densities: vec![0.0; Self::sample_count(size)?],
+                        iso: 0.5,
+                    });
+                }
+                b"XYZI" if !filled => {
+                    let Some(volume) = volume.as_mut() else {
+                        return Err(invalid_data("voxels found before the size of the model"));
+                    };
+
+                    let count = content.read_u32()?;
+                    for _ in 0..count {
+                        let [x, y, z, _color] = <[u8; 4]>::try_from(content.take(4)?).unwrap();
+                        let (x, y, z) = (x as u32, y as u32, z as u32);
+                        if x >= volume.size.x || y >= volume.size.z || z >= volume.size.y {
+                            continue;
+                        }
+                        // A rotation around X, swapping Y and Z alone would mirror the model
+                        let i = volume.index(UVec3::new(x, z, volume.size.z - 1 - y));
+                        volume.densities[i] = 1.0;
+                    }
+                    filled = true;
+                }
+                _ => {}
+            }
+        }
+
+        volume.ok_or_else(|| invalid_data("no model in the MagicaVoxel file"))
+    }
+
+    pub fn size(&self) -> UVec3 {
+        self.size
+    }
+
+    /// Number of samples of a volume of `size`, which must be neither empty nor too large to
+    /// allocate
+    fn sample_count(size: UVec3) -> io::Result<usize> {
+        let count = size.x as u64 * size.y as u64 * size.z as u64;
+        if count == 0 || count > MAX_VOLUME_SAMPLES {
+            return Err(invalid_data(format!(
+                "a volume must have between 1 and {MAX_VOLUME_SAMPLES} samples, got {}x{}x{}",
+                size.x, size.y, size.z
+            )));
+        }
+        Ok(count as usize)
+    }
+
+    fn index(&self, p: UVec3) -> usize {
+        ((p.z * self.size.y + p.y) * self.size.x + p.x) as usize
+    }
+
+    fn density(&self, p: IVec3) -> f32 {
+        if p.cmplt(IVec3::ZERO).any() || p.cmpge(self.size.as_ivec3()).any() {
+            return 0.0;
+        }
+        self.densities[self.index(p.as_uvec3())]
+    }
+
+    /// Trilinear density at `p` in samples, zero outside of the volume
+    pub fn sample(&self, p: Vec3) -> f32 {
+        let cell = p.floor();
+        let t = p - cell;
+        let cell = cell.as_ivec3();
+
+        let mut density = 0.0;
+        for corner in 0..8 {
+            let offset = IVec3::new(corner & 1, (corner >> 1) & 1, (corner >> 2) & 1);
+            let w = offset.as_vec3() * t + (Vec3::ONE - offset.as_vec3()) * (Vec3::ONE - t);
+            density += self.density(cell + offset) * w.x * w.y * w.z;
+        }
+        density
+    }
+}
+
+struct VoxReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> VoxReader<'a> {
+    fn take(&mut self, n: usize) -> io::Result<&'a [u8]> {
+        if self.bytes.len() < n {
+            return Err(io::Error::from(ErrorKind::UnexpectedEof));
+        }
+        let (taken, rest) = self.bytes.split_at(n);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn read_u32(&mut self) -> io::Result<u32> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+}
+
+/// Volume placed in the world with its first sample at `origin`, one sample every `sample_size`
+/// voxels
+#[derive(Debug, Clone)]
+pub struct VolumeGenerator {
+    pub volume: DensityVolume,
+    pub origin: Vec3,
+    pub sample_size: f32,
+}
+
+impl ChunkGenerator for VolumeGenerator {
+    /// Densities aren't distances, the difference to the iso value is divided by the slope of the
+    /// density to get an approximate distance near the surface
+    fn signed_distance(&self, p: Vec3) -> f32 {
+        const H: f32 = 0.5;
+
+        let q = (p - self.origin) / self.sample_size;
+        let volume = &self.volume;
+
+        let gradient = Vec3::new(
+            volume.sample(q + Vec3::X * H) - volume.sample(q - Vec3::X * H),
+            volume.sample(q + Vec3::Y * H) - volume.sample(q - Vec3::Y * H),
+            volume.sample(q + Vec3::Z * H) - volume.sample(q - Vec3::Z * H),
+        ) / (2.0 * H);
+
+        // Flat regions are far from the surface, the slope only bounds the distance from below
+        let slope = gradient.length().max(0.1);
+        (volume.iso - volume.sample(q)) / slope * self.sample_size
+    }
+}
@@ -1,66 +1,166 @@
+mod biomes;
+mod caves;
+mod erosion;
 mod generator;
+mod import;
+mod noise;
 mod sdf;
+mod voxelizer;
 
-use std::sync::Arc;
+use std::{sync::Arc, time::Instant};
 
 use bevy::{
     prelude::*,
-    tasks::{TaskPool, TaskPoolBuilder},
+    tasks::{futures_lite::future, AsyncComputeTaskPool, Task},
 };
 use crossbeam_queue::SegQueue;
 use fast_surface_nets::ndshape::ConstShape;
+use float_ord::FloatOrd;
+use rand::{rngs::StdRng, SeedableRng};
 use tracing::Instrument;
 
 use crate::{
-    chunk::{Chunk, ChunkKey, ChunkShape, Extent3i, CHUNK_SIZE},
-    chunk_map::{ChunkCommand, ChunkCommandQueue, ChunkMap, CurrentChunks, DirtyChunks},
-    generation::generator::GENERATOR,
+    chunk::{Chunk, ChunkKey, ChunkShape, ChunkSummary, Extent3i, CHUNK_SIZE},
+    chunk_map::{
+        ChunkCommand, ChunkCommandQueue, ChunkGenerated, ChunkMap, ChunkState, ChunkUnloaded,
+        CurrentChunks, DirtyChunks,
+    },
+    priority::{chunk_priority, ChunkViewer, PrioritySettings, SurfaceHint},
+    scheduling::{SchedulingPlugin, VoxelTaskPools, VoxelTaskStats},
     LEVEL_OF_DETAIL,
 };
 
-pub struct GenerationPlugin;
+pub use biomes::{Biome, BiomeMap, BiomeParams};
+pub use caves::{CaveKind, CaveLayer};
+pub use erosion::{CubeSphereHeightmap, ErosionSettings};
+pub use generator::{world_generator, ChunkGenerator, Generator, GENERATOR, SPHERE_RADIUS};
+pub use import::{
+    DensityVolume, Heightmap, HeightmapGenerator, HeightmapProjection, VolumeGenerator,
+};
+pub use noise::{Curve, NoiseLayer};
+pub use voxelizer::{CsgOperation, MeshSdf, MeshStamp};
+
+pub struct GenerationPlugin {
+    /// Simulates the erosion in the background and regenerates the chunks with it, the world has
+    /// no erosion otherwise
+    pub erosion: bool,
+}
 
 impl Plugin for GenerationPlugin {
     fn build(&self, app: &mut App) {
-        app.init_resource::<ChunkMap>()
+        if !app.is_plugin_added::<SchedulingPlugin>() {
+            app.add_plugins(SchedulingPlugin);
+        }
+
+        app.init_resource::<WorldSeed>()
+            .register_type::<WorldSeed>()
+            .init_resource::<ChunkMap>()
             .init_resource::<ChunkCommandQueue>()
             .init_resource::<CurrentChunks>()
             .init_resource::<DirtyChunks>()
-            .init_resource::<GenerationTaskPool>()
             .init_resource::<GenerationResults>()
+            .init_resource::<InitialChunksExtent>()
+            .init_resource::<WorldGenerator>()
+            .init_resource::<ErosionTask>()
+            .register_type::<ChunkState>()
+            .add_event::<ChunkGenerated>()
+            .add_event::<ChunkUnloaded>()
             .add_systems(Startup, request_chunks)
             .add_systems(
                 Update,
                 (
-                    spawn_chunk_generation_tasks
-                        .run_if(|r: Res<ChunkCommandQueue>| !r.is_create_empty()),
+                    finish_erosion_task.run_if(|r: Res<ErosionTask>| r.0.is_some()),
+                    unload_chunks.run_if(|r: Res<ChunkCommandQueue>| !r.is_delete_empty()),
+                    spawn_queued_chunks.run_if(|r: Res<ChunkCommandQueue>| !r.is_create_empty()),
+                    spawn_chunk_generation_tasks,
                     handle_chunk_generation_results
                         .run_if(|r: Res<GenerationResults>| !r.is_empty()),
-                ),
+                )
+                    // The spawned entities must exist when their generation results are handled
+                    .chain(),
             );
+
+        if self.erosion {
+            app.add_systems(Startup, spawn_erosion_task);
+        }
     }
 }
 
-#[derive(Resource, Deref)]
-struct GenerationTaskPool(TaskPool);
+#[derive(Resource, Deref, Default)]
+pub struct GenerationResults(Arc<SegQueue<(ChunkKey, Chunk, ChunkSummary)>>);
 
-impl Default for GenerationTaskPool {
+/// Seed of the world, every random step of the generation derives its own seed from it
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Hash, Deref, Reflect)]
+pub struct WorldSeed(pub u64);
+
+impl Default for WorldSeed {
     fn default() -> Self {
-        Self(
-            TaskPoolBuilder::default()
-                .num_threads(2)
-                .thread_name("Generation Task Pool (2 threads)".to_string())
-                .build(),
+        Self(43210)
+    }
+}
+
+impl WorldSeed {
+    /// Seed of the `n`th random source of the world, mixed so that the sources of nearby seeds
+    /// and indices don't overlap
+    pub fn derive(self, n: u64) -> u64 {
+        splitmix64(self.0 ^ n.wrapping_mul(0x9e37_79b9_7f4a_7c15))
+    }
+
+    /// Random number generator of a chunk, the same for every run and every thread count
+    pub fn chunk_rng(self, key: ChunkKey) -> StdRng {
+        let [x, y, z] = key.0.to_array().map(|c| c as u32 as u64);
+        StdRng::seed_from_u64(
+            self.0
+                ^ x.wrapping_mul(0x9e37_79b9_7f4a_7c15)
+                ^ y.wrapping_mul(0xc2b2_ae3d_27d4_eb4f)
+                ^ z.wrapping_mul(0x1656_67b1_9e37_79f9),
         )
     }
 }
 
-#[derive(Resource, Deref, Default)]
-pub struct GenerationResults(Arc<SegQueue<(ChunkKey, Chunk)>>);
+/// Finalizer of SplitMix64, consecutive inputs give unrelated outputs
+pub(crate) fn splitmix64(x: u64) -> u64 {
+    let x = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    let x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
+
+/// Generator used for the new chunks, the procedural generator of the [`WorldSeed`] by default
+#[derive(Resource, Deref)]
+pub struct WorldGenerator(pub Arc<dyn ChunkGenerator>);
+
+impl FromWorld for WorldGenerator {
+    fn from_world(world: &mut World) -> Self {
+        let seed = *world.get_resource_or_insert_with(WorldSeed::default);
+        Self(world_generator(seed))
+    }
+}
+
+/// Erosion of the procedural world, simulated in the background so the app starts with the world
+/// without erosion
+#[derive(Resource, Default)]
+pub struct ErosionTask(Option<Task<Generator>>);
+
+/// Chunks requested on startup
+#[derive(Resource, Deref)]
+pub struct InitialChunksExtent(pub Extent3i);
+
+impl Default for InitialChunksExtent {
+    fn default() -> Self {
+        Self(Extent3i::from_min_and_lub(
+            IVec3::splat((-10.0 / LEVEL_OF_DETAIL).floor() as i32),
+            IVec3::splat((10.0 / LEVEL_OF_DETAIL).ceil() as i32),
+        ))
+        // Self(Extent3i::from_min_and_lub(IVec3::splat(-20), IVec3::splat(20)))
+        // Self(Extent3i::from_min_and_lub(IVec3::new(-20, -5, -20), IVec3::new(0, 0, 0)))
+    }
+}
 
 fn request_chunks(
     mut chunk_command_queue: ResMut<ChunkCommandQueue>,
     current_chunks: Res<CurrentChunks>,
+    chunks_extent: Res<InitialChunksExtent>,
 ) {
     info!(
         "Chunk size: {}x{}x{}",
@@ -69,13 +169,6 @@ fn request_chunks(
         ChunkShape::ARRAY[2],
     );
 
-    let chunks_extent = Extent3i::from_min_and_lub(
-        IVec3::splat((-10.0 / LEVEL_OF_DETAIL).floor() as i32),
-        IVec3::splat((10.0 / LEVEL_OF_DETAIL).ceil() as i32),
-    );
-    // let chunks_extent = Extent3i::from_min_and_lub(IVec3::splat(-20), IVec3::splat(20));
-    // let chunks_extent = Extent3i::from_min_and_lub(IVec3::new(-20, -5, -20), IVec3::new(0, 0, 0));
-
     let chunk_count = chunks_extent.num_points();
 
     chunks_extent
@@ -84,9 +177,6 @@ fn request_chunks(
         .filter(|&k| !current_chunks.contains(k))
         .for_each(|key| chunk_command_queue.push(ChunkCommand::Create(key)));
 
-    // TODO: replace with camera position
-    chunk_command_queue.sort_by_distance(ChunkKey(IVec3::ZERO));
-
     let point_count = chunk_count * (CHUNK_SIZE as u64);
 
     info!(
@@ -94,38 +184,169 @@ fn request_chunks(
     );
 }
 
-fn spawn_chunk_generation_tasks(
-    gen_pool: Res<GenerationTaskPool>,
+fn spawn_erosion_task(mut erosion_task: ResMut<ErosionTask>, seed: Res<WorldSeed>) {
+    let seed = *seed;
+    erosion_task.0 = Some(AsyncComputeTaskPool::get().spawn(async move {
+        let start = Instant::now();
+        let generator = Generator::for_world(seed);
+        info!("Simulated the erosion in {:?}", start.elapsed());
+        generator
+    }));
+}
+
+/// Replaces the generator by the eroded one and regenerates the loaded chunks. It waits for the
+/// running generation tasks so that no chunk of the previous generator arrives afterwards.
+fn finish_erosion_task(
+    mut erosion_task: ResMut<ErosionTask>,
+    mut generator: ResMut<WorldGenerator>,
+    mut chunk_command_queue: ResMut<ChunkCommandQueue>,
+    current_chunks: Res<CurrentChunks>,
+    stats: Res<VoxelTaskStats>,
+) {
+    if stats.generation.in_flight > 0 {
+        return;
+    }
+    let task = erosion_task.0.as_mut().unwrap();
+    let Some(eroded) = future::block_on(future::poll_once(task)) else {
+        return;
+    };
+    erosion_task.0 = None;
+    generator.0 = Arc::new(eroded);
+
+    for key in current_chunks.keys() {
+        chunk_command_queue.push(ChunkCommand::Delete(key));
+        chunk_command_queue.push(ChunkCommand::Create(key));
+    }
+}
+
+fn unload_chunks(
     mut commands: Commands,
     mut chunk_command_queue: ResMut<ChunkCommandQueue>,
+    chunk_map: Res<ChunkMap>,
     mut current_chunks: ResMut<CurrentChunks>,
-    gen_results: Res<GenerationResults>,
+    mut dirty_chunks: ResMut<DirtyChunks>,
+    mut states: Query<&mut ChunkState>,
+    mut unloaded: EventWriter<ChunkUnloaded>,
 ) {
-    chunk_command_queue.drain_create_commands().for_each(|key| {
-        let entity = commands.spawn((Name::new("Chunk"), key)).id();
+    for key in chunk_command_queue.drain_delete_commands() {
+        let Some(entity) = current_chunks.remove(key) else {
+            continue;
+        };
+
+        if let Ok(mut state) = states.get_mut(entity) {
+            if let Err(error) = state.transition(ChunkState::Unloading) {
+                warn!("{error} for chunk {key:?}");
+            }
+        }
+
+        // Results of tasks still running for this chunk are dropped when they arrive
+        chunk_map.storage.remove(&key);
+        dirty_chunks.remove(&key);
+        commands.entity(entity).despawn_recursive();
+        unloaded.send(ChunkUnloaded { key });
+    }
+}
+
+fn spawn_queued_chunks(
+    mut commands: Commands,
+    mut chunk_command_queue: ResMut<ChunkCommandQueue>,
+    mut current_chunks: ResMut<CurrentChunks>,
+    generator: Res<WorldGenerator>,
+) {
+    for key in chunk_command_queue.drain_create_commands() {
+        if current_chunks.contains(key) {
+            continue;
+        }
+
+        let entity = commands
+            .spawn((
+                Name::new("Chunk"),
+                key,
+                ChunkState::Queued,
+                SurfaceHint::new(key, generator.0.as_ref()),
+            ))
+            .id();
         current_chunks.add(key, entity);
+    }
+}
+
+fn spawn_chunk_generation_tasks(
+    pools: Res<VoxelTaskPools>,
+    mut stats: ResMut<VoxelTaskStats>,
+    mut chunks: Query<(&ChunkKey, &mut ChunkState, &SurfaceHint)>,
+    gen_results: Res<GenerationResults>,
+    generator: Res<WorldGenerator>,
+    viewer: Res<ChunkViewer>,
+    priority_settings: Res<PrioritySettings>,
+) {
+    let mut queued: Vec<_> = chunks
+        .iter_mut()
+        .filter(|(_, state, _)| **state == ChunkState::Queued)
+        .map(|(&key, state, &surface)| {
+            let priority = chunk_priority(key, surface, &viewer, &priority_settings);
+            (FloatOrd(priority), key, state)
+        })
+        .collect();
+    stats.generation.queued = queued.len();
+
+    // Only the chunks that get a task are sorted, the other ones stay queued until tasks finish
+    let available = stats.generation.available().min(queued.len());
+    if available == 0 {
+        return;
+    }
+    queued.select_nth_unstable_by_key(available - 1, |&(priority, ..)| priority);
+    queued.truncate(available);
+    queued.sort_unstable_by_key(|&(priority, ..)| priority);
+
+    for (_, key, mut state) in queued {
+        state.transition(ChunkState::Generating).unwrap();
+        stats.generation.queued -= 1;
 
         let gen_results = Arc::clone(&gen_results);
+        let generator = Arc::clone(&generator);
+        let latencies = stats.generation.start_task();
+        let spawned = Instant::now();
 
-        gen_pool
+        pools
+            .generation()
             .spawn(
                 async move {
-                    let chunk_data = GENERATOR.generate_chunk(key);
-                    gen_results.push((key, chunk_data));
+                    let chunk_data = generator.generate_chunk(key);
+                    latencies.record(spawned.elapsed());
+                    let summary = ChunkSummary::from_chunk(&chunk_data);
+                    gen_results.push((key, chunk_data, summary));
                 }
                 .instrument(trace_span!("chunk_generation_task")),
             )
             .detach();
-    });
+    }
 }
 
 fn handle_chunk_generation_results(
-    mut chunk_map: ResMut<ChunkMap>,
+    chunk_map: Res<ChunkMap>,
     mut dirty_chunks: ResMut<DirtyChunks>,
+    current_chunks: Res<CurrentChunks>,
+    mut states: Query<&mut ChunkState>,
     gen_results: Res<GenerationResults>,
+    mut stats: ResMut<VoxelTaskStats>,
+    mut generated: EventWriter<ChunkGenerated>,
 ) {
-    while let Some((key, chunk_data)) = gen_results.pop() {
-        chunk_map.storage.insert(key, chunk_data);
-        dirty_chunks.insert(key);
+    while let Some((key, chunk_data, summary)) = gen_results.pop() {
+        stats.generation.finish_task();
+
+        // The chunk was unloaded while it was generating
+        let Some(entity) = current_chunks.get_entity(key) else {
+            continue;
+        };
+        let Ok(mut state) = states.get_mut(entity) else {
+            continue;
+        };
+        if let Err(error) = state.transition(ChunkState::Generated) {
+            warn!("{error} for chunk {key:?}");
+            continue;
+        }
+
+        chunk_map.insert_generated(key, chunk_data, summary, &mut dirty_chunks);
+        generated.send(ChunkGenerated { key, entity });
     }
 }
@@ -0,0 +1,182 @@
+use bevy::prelude::*;
+use bracket_noise::prelude::{FastNoise, FractalType, NoiseType};
+
+/// Offsets between the three samples of a warp field, so each axis is displaced independently.
+/// They are applied in the space of the noises, after the frequency, so that they keep the samples
+/// apart whatever the frequency of the warp layer.
+const WARP_OFFSETS: [Vec3; 3] = [
+    Vec3::ZERO,
+    Vec3::new(31.4, -47.2, 12.9),
+    Vec3::new(-18.6, 25.3, 63.1),
+];
+
+/// Node of a noise stack, sampled recursively. The layers are built with the constructors and
+/// combinators below, e.g. `NoiseLayer::fractal(..).warp(NoiseLayer::simplex(..), 40.0)`.
+pub enum NoiseLayer {
+    Constant(f32),
+    Noise {
+        noise: FastNoise,
+        frequency: f32,
+    },
+    Add(Box<NoiseLayer>, Box<NoiseLayer>),
+    Multiply(Box<NoiseLayer>, Box<NoiseLayer>),
+    Max(Box<NoiseLayer>, Box<NoiseLayer>),
+    /// Blends from `a` to `b` as the mask goes from 0 to 1
+    Mask {
+        a: Box<NoiseLayer>,
+        b: Box<NoiseLayer>,
+        mask: Box<NoiseLayer>,
+    },
+    /// Samples `source` at a position displaced by `warp` times `amplitude` on each axis, `warp` is
+    /// sampled at a different offset for each axis
+    Warp {
+        source: Box<NoiseLayer>,
+        warp: Box<NoiseLayer>,
+        amplitude: f32,
+    },
+    Curve {
+        source: Box<NoiseLayer>,
+        curve: Curve,
+    },
+}
+
+impl NoiseLayer {
+    pub fn simplex(seed: u64, frequency: f32) -> Self {
+        let mut noise = FastNoise::new();
+        noise.set_seed(seed);
+        noise.set_noise_type(NoiseType::Simplex);
+
+        Self::Noise { noise, frequency }
+    }
+
+    pub fn fractal(seed: u64, frequency: f32, fractal_type: FractalType, octaves: i32) -> Self {
+        let mut noise = FastNoise::new();
+        noise.set_seed(seed);
+        noise.set_noise_type(NoiseType::SimplexFractal);
+        noise.set_fractal_type(fractal_type);
+        noise.set_fractal_octaves(octaves);
+
+        Self::Noise { noise, frequency }
+    }
+
+    /// Sets the gain between the octaves of a fractal noise, the higher the rougher
+    pub fn with_gain(mut self, gain: f32) -> Self {
+        if let Self::Noise { noise, .. } = &mut self {
+            noise.set_fractal_gain(gain);
+        }
+        self
+    }
+
+    pub fn add(self, other: NoiseLayer) -> Self {
+        Self::Add(Box::new(self), Box::new(other))
+    }
+
+    pub fn mul(self, other: NoiseLayer) -> Self {
+        Self::Multiply(Box::new(self), Box::new(other))
+    }
+
+    pub fn scale(self, factor: f32) -> Self {
+        self.mul(Self::Constant(factor))
+    }
+
+    pub fn max(self, other: NoiseLayer) -> Self {
+        Self::Max(Box::new(self), Box::new(other))
+    }
+
+    /// Blends from `self` to `other` where `mask` goes from 0 to 1
+    pub fn mask(self, other: NoiseLayer, mask: NoiseLayer) -> Self {
+        Self::Mask {
+            a: Box::new(self),
+            b: Box::new(other),
+            mask: Box::new(mask),
+        }
+    }
+
+    pub fn warp(self, warp: NoiseLayer, amplitude: f32) -> Self {
+        Self::Warp {
+            source: Box::new(self),
+            warp: Box::new(warp),
+            amplitude,
+        }
+    }
+
+    pub fn curve(self, points: impl Into<Vec<(f32, f32)>>) -> Self {
+        Self::Curve {
+            source: Box::new(self),
+            curve: Curve::new(points),
+        }
+    }
+
+    pub fn sample(&self, p: Vec3) -> f32 {
+        self.sample_shifted(p, Vec3::ZERO)
+    }
+
+    /// Samples the layer with its noises shifted by `shift`, in the space of the noises
+    fn sample_shifted(&self, p: Vec3, shift: Vec3) -> f32 {
+        match self {
+            Self::Constant(value) => *value,
+            Self::Noise { noise, frequency } => {
+                let [x, y, z] = (p * *frequency + shift).to_array();
+                noise.get_noise3d(x, y, z)
+            }
+            Self::Add(a, b) => a.sample_shifted(p, shift) + b.sample_shifted(p, shift),
+            Self::Multiply(a, b) => a.sample_shifted(p, shift) * b.sample_shifted(p, shift),
+            Self::Max(a, b) => a.sample_shifted(p, shift).max(b.sample_shifted(p, shift)),
+            Self::Mask { a, b, mask } => {
+                let t = mask.sample_shifted(p, shift).clamp(0.0, 1.0);
+                // Skip the layer that doesn't contribute, masks are mostly saturated
+                if t <= 0.0 {
+                    a.sample_shifted(p, shift)
+                } else if t >= 1.0 {
+                    b.sample_shifted(p, shift)
+                } else {
+                    a.sample_shifted(p, shift) * (1.0 - t) + b.sample_shifted(p, shift) * t
+                }
+            }
+            Self::Warp {
+                source,
+                warp,
+                amplitude,
+            } => {
+                let offset = Vec3::from(WARP_OFFSETS.map(|o| warp.sample_shifted(p, shift + o)));
+                source.sample_shifted(p + offset * *amplitude, shift)
+            }
+            Self::Curve { source, curve } => curve.evaluate(source.sample_shifted(p, shift)),
+        }
+    }
+}
+
+/// Piecewise linear remapping, constant outside of its first and last points. The points must have
+/// distinct x coordinates.
+pub struct Curve {
+    points: Vec<(f32, f32)>,
+}
+
+impl Curve {
+    pub fn new(points: impl Into<Vec<(f32, f32)>>) -> Self {
+        let mut points = points.into();
+        assert!(!points.is_empty(), "a curve needs at least one point");
+        points.sort_by(|a, b| a.0.total_cmp(&b.0));
+        assert!(
+            points.windows(2).all(|w| w[0].0 < w[1].0),
+            "the points of a curve must have distinct x coordinates"
+        );
+
+        Self { points }
+    }
+
+    pub fn evaluate(&self, x: f32) -> f32 {
+        let i = self.points.partition_point(|&(px, _)| px < x);
+
+        if i == 0 {
+            return self.points[0].1;
+        }
+        if i == self.points.len() {
+            return self.points[i - 1].1;
+        }
+
+        let (x0, y0) = self.points[i - 1];
+        let (x1, y1) = self.points[i];
+        y0 + (y1 - y0) * (x - x0) / (x1 - x0)
+    }
+}
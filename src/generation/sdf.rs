@@ -12,6 +12,12 @@ pub fn infinite_repetition(p: Vec3, c: Vec3, primitive: impl Fn(Vec3) -> f32) ->
     primitive(q)
 }
 
+/// Removes the shape `b` from `a`
+#[inline]
+pub fn subtraction(a: f32, b: f32) -> f32 {
+    a.max(-b)
+}
+
 // Others
 
 /// From: https://registry.khronos.org/OpenGL-Refpages/gl4/html/mod.xhtml
@@ -0,0 +1,426 @@
+use std::{
+    fs,
+    io::{self, ErrorKind},
+    path::Path,
+    sync::Arc,
+};
+
+use bevy::prelude::*;
+
+use super::sdf;
+
+/// Distance from the surface, in voxels of the grid, up to which the distances are exact. The
+/// chunks only store one voxel on each side of the surface, further values are clamped to it.
+const BAND: f32 = 2.0;
+
+/// Largest grid a mesh is voxelized into, 256 MiB of distances
+const MAX_GRID_POINTS: f32 = (1 << 26) as f32;
+
+fn invalid_data(error: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, error)
+}
+
+fn invalid_input(error: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> io::Error {
+    io::Error::new(ErrorKind::InvalidInput, error)
+}
+
+/// Signed distance to a closed triangle mesh, precomputed on a grid around it
+#[derive(Debug, Clone)]
+pub struct MeshSdf {
+    origin: Vec3,
+    size: UVec3,
+    voxel_size: f32,
+    distances: Vec<f32>,
+}
+
+impl MeshSdf {
+    /// Loads an OBJ or binary glTF (`.glb`) file and voxelizes it, the mesh must be closed for the
+    /// inside to be known
+    pub fn load(path: &Path, voxel_size: f32) -> io::Result<Self> {
+        let bytes = fs::read(path)?;
+        let is_glb = path
+            .extension()
+            .map_or(false, |e| e.eq_ignore_ascii_case("glb"));
+
+        let (positions, triangles) = if is_glb {
+            parse_glb(&bytes)?
+        } else {
+            parse_obj(&String::from_utf8_lossy(&bytes))?
+        };
+
+        Self::voxelize(&positions, &triangles, voxel_size)
+    }
+
+    /// Fails if the mesh has no triangles, or if `voxel_size` isn't positive or is too small for
+    /// the grid to fit in memory
+    pub fn voxelize(
+        positions: &[Vec3],
+        triangles: &[[u32; 3]],
+        voxel_size: f32,
+    ) -> io::Result<Self> {
+        if voxel_size <= 0.0 || !voxel_size.is_finite() {
+            return Err(invalid_input(format!(
+                "the voxel size must be positive, got {voxel_size}"
+            )));
+        }
+        if triangles.is_empty() {
+            return Err(invalid_data("the mesh has no triangles"));
+        }
+        if triangles
+            .iter()
+            .flatten()
+            .any(|&i| i as usize >= positions.len())
+        {
+            return Err(invalid_data("triangle index out of range"));
+        }
+
+        let (min, max) = positions.iter().fold(
+            (Vec3::splat(f32::INFINITY), Vec3::splat(f32::NEG_INFINITY)),
+            |(min, max), &p| (min.min(p), max.max(p)),
+        );
+
+        // Enough room around the mesh for the outside of the band to stay in the grid
+        let padding = BAND + 1.0;
+        let origin = min - padding * voxel_size;
+        let size = ((max - min) / voxel_size + 2.0 * padding).ceil() + 1.0;
+        let points = size.x * size.y * size.z;
+        // NaN if the mesh has non-finite positions
+        if points.is_nan() || points > MAX_GRID_POINTS {
+            return Err(invalid_input(format!(
+                "a voxel size of {voxel_size} needs a grid of {size}, the mesh is too large"
+            )));
+        }
+        let size = size.as_uvec3();
+
+        let mut grid = Self {
+            origin,
+            size,
+            voxel_size,
+            distances: vec![BAND * voxel_size; (size.x * size.y * size.z) as usize],
+        };
+
+        let triangles: Vec<[Vec3; 3]> = triangles
+            .iter()
+            .map(|t| t.map(|i| positions[i as usize]))
+            .collect();
+
+        grid.compute_band_distances(&triangles);
+        grid.compute_signs(&triangles);
+        Ok(grid)
+    }
+
+    fn index(&self, p: UVec3) -> usize {
+        ((p.z * self.size.y + p.y) * self.size.x + p.x) as usize
+    }
+
+    fn point(&self, p: UVec3) -> Vec3 {
+        self.origin + p.as_vec3() * self.voxel_size
+    }
+
+    /// Exact unsigned distances of the grid points within the band around each triangle
+    fn compute_band_distances(&mut self, triangles: &[[Vec3; 3]]) {
+        let band = BAND * self.voxel_size;
+        let max_index = self.size.as_ivec3() - 1;
+
+        for triangle in triangles {
+            let min = triangle[0].min(triangle[1]).min(triangle[2]) - band;
+            let max = triangle[0].max(triangle[1]).max(triangle[2]) + band;
+            let min = ((min - self.origin) / self.voxel_size).floor().as_ivec3();
+            let max = ((max - self.origin) / self.voxel_size).ceil().as_ivec3();
+            let min = min.clamp(IVec3::ZERO, max_index).as_uvec3();
+            let max = max.clamp(IVec3::ZERO, max_index).as_uvec3();
+
+            for z in min.z..=max.z {
+                for y in min.y..=max.y {
+                    for x in min.x..=max.x {
+                        let p = UVec3::new(x, y, z);
+                        let point = self.point(p);
+                        let distance = point.distance(closest_point_on_triangle(point, triangle));
+
+                        let i = self.index(p);
+                        self.distances[i] = self.distances[i].min(distance);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Flips the sign of the grid points inside the mesh, found by counting the crossings of a ray
+    /// cast along X for every row of the grid
+    fn compute_signs(&mut self, triangles: &[[Vec3; 3]]) {
+        // Keeps the rays away from the edges and vertices shared by the triangles, which would be
+        // counted twice
+        let jitter = Vec2::new(1.234e-4, 2.345e-4) * self.voxel_size;
+
+        let mut crossings = Vec::new();
+        for z in 0..self.size.z {
+            for y in 0..self.size.y {
+                let row = self.point(UVec3::new(0, y, z));
+                let ray = Vec2::new(row.y, row.z) + jitter;
+
+                crossings.clear();
+                crossings.extend(triangles.iter().filter_map(|t| ray_crossing(ray, t)));
+                crossings.sort_by(f32::total_cmp);
+
+                let mut crossed = 0;
+                for x in 0..self.size.x {
+                    let px = self.origin.x + x as f32 * self.voxel_size;
+                    while crossed < crossings.len() && crossings[crossed] < px {
+                        crossed += 1;
+                    }
+
+                    if crossed % 2 == 1 {
+                        let i = self.index(UVec3::new(x, y, z));
+                        self.distances[i] = -self.distances[i];
+                    }
+                }
+            }
+        }
+    }
+
+    /// Minimum and maximum corners of the grid
+    pub fn bounds(&self) -> (Vec3, Vec3) {
+        (
+            self.origin,
+            self.origin + (self.size - 1).as_vec3() * self.voxel_size,
+        )
+    }
+
+    /// Trilinear signed distance at `p`, only exact within a couple of voxels of the surface
+    pub fn sample(&self, p: Vec3) -> f32 {
+        let (min, max) = self.bounds();
+        let outside = (min - p).max(p - max).max(Vec3::ZERO).length();
+        if outside > 0.0 {
+            return outside + BAND * self.voxel_size;
+        }
+
+        let q = ((p - self.origin) / self.voxel_size).min((self.size - 1).as_vec3() - 1e-3);
+        let cell = q.floor();
+        let t = q - cell;
+        let cell = cell.as_uvec3();
+
+        let mut distance = 0.0;
+        for corner in 0..8 {
+            let offset = UVec3::new(corner & 1, (corner >> 1) & 1, (corner >> 2) & 1);
+            let w = offset.as_vec3() * t + (Vec3::ONE - offset.as_vec3()) * (Vec3::ONE - t);
+            distance += self.distances[self.index(cell + offset)] * w.x * w.y * w.z;
+        }
+        distance
+    }
+}
+
+/// X coordinate where the line parallel to X through `ray` (in YZ) crosses the triangle
+fn ray_crossing(ray: Vec2, [a, b, c]: &[Vec3; 3]) -> Option<f32> {
+    let (a2, b2, c2) = (
+        Vec2::new(a.y, a.z),
+        Vec2::new(b.y, b.z),
+        Vec2::new(c.y, c.z),
+    );
+    let area = (b2 - a2).perp_dot(c2 - a2);
+    if area.abs() < f32::EPSILON {
+        return None;
+    }
+
+    let u = (c2 - b2).perp_dot(ray - b2) / area;
+    let v = (a2 - c2).perp_dot(ray - c2) / area;
+    let w = 1.0 - u - v;
+
+    (u >= 0.0 && v >= 0.0 && w >= 0.0).then(|| u * a.x + v * b.x + w * c.x)
+}
+
+/// From "Real-Time Collision Detection", Christer Ericson
+fn closest_point_on_triangle(p: Vec3, [a, b, c]: &[Vec3; 3]) -> Vec3 {
+    let (a, b, c) = (*a, *b, *c);
+    let ab = b - a;
+    let ac = c - a;
+    let ap = p - a;
+
+    let d1 = ab.dot(ap);
+    let d2 = ac.dot(ap);
+    if d1 <= 0.0 && d2 <= 0.0 {
+        return a;
+    }
+
+    let bp = p - b;
+    let d3 = ab.dot(bp);
+    let d4 = ac.dot(bp);
+    if d3 >= 0.0 && d4 <= d3 {
+        return b;
+    }
+
+    let vc = d1 * d4 - d3 * d2;
+    if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+        return a + ab * (d1 / (d1 - d3));
+    }
+
+    let cp = p - c;
+    let d5 = ab.dot(cp);
+    let d6 = ac.dot(cp);
+    if d6 >= 0.0 && d5 <= d6 {
+        return c;
+    }
+
+    let vb = d5 * d2 - d1 * d6;
+    if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+        return a + ac * (d2 / (d2 - d6));
+    }
+
+    let va = d3 * d6 - d5 * d4;
+    if va <= 0.0 && d4 - d3 >= 0.0 && d5 - d6 >= 0.0 {
+        return b + (c - b) * ((d4 - d3) / ((d4 - d3) + (d5 - d6)));
+    }
+
+    let denom = 1.0 / (va + vb + vc);
+    a + ab * (vb * denom) + ac * (vc * denom)
+}
+
+/// Vertices and faces of an OBJ file, the polygons are triangulated as fans
+fn parse_obj(source: &str) -> io::Result<(Vec<Vec3>, Vec<[u32; 3]>)> {
+    let mut positions = Vec::new();
+    let mut triangles = Vec::new();
+
+    for (line_number, line) in source.lines().enumerate() {
+        let error = |message: &str| invalid_data(format!("line {}: {message}", line_number + 1));
+        let mut tokens = line.split_whitespace();
+
+        match tokens.next() {
+            Some("v") => {
+                let coordinates = tokens
+                    .take(3)
+                    .map(|t| t.parse::<f32>())
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|_| error("invalid vertex"))?;
+                let [x, y, z] = coordinates[..] else {
+                    return Err(error("a vertex needs three coordinates"));
+                };
+                positions.push(Vec3::new(x, y, z));
+            }
+            Some("f") => {
+                let indices = tokens
+                    .map(|t| {
+                        // Only the position of `v/vt/vn`, negative indices count from the end
+                        let index: i64 = t.split('/').next()?.parse().ok()?;
+                        let index = if index < 0 {
+                            positions.len() as i64 + index
+                        } else {
+                            index - 1
+                        };
+                        (0..positions.len() as i64)
+                            .contains(&index)
+                            .then_some(index as u32)
+                    })
+                    .collect::<Option<Vec<_>>>()
+                    .ok_or_else(|| error("invalid face"))?;
+
+                for i in 1..indices.len().saturating_sub(1) {
+                    triangles.push([indices[0], indices[i], indices[i + 1]]);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok((positions, triangles))
+}
+
+/// Triangles of the meshes of the default scene of a binary glTF, in the space of the scene
+fn parse_glb(bytes: &[u8]) -> io::Result<(Vec<Vec3>, Vec<[u32; 3]>)> {
+    let gltf = gltf::Gltf::from_slice(bytes).map_err(invalid_data)?;
+    let blob = gltf.blob.as_deref();
+
+    let mut positions = Vec::new();
+    let mut triangles = Vec::new();
+
+    let scene = gltf
+        .default_scene()
+        .or_else(|| gltf.scenes().next())
+        .ok_or_else(|| invalid_data("the glTF file has no scene"))?;
+
+    let mut nodes: Vec<_> = scene.nodes().map(|n| (n, Mat4::IDENTITY)).collect();
+    while let Some((node, parent_transform)) = nodes.pop() {
+        let transform = parent_transform * Mat4::from_cols_array_2d(&node.transform().matrix());
+        nodes.extend(node.children().map(|child| (child, transform)));
+
+        let Some(mesh) = node.mesh() else {
+            continue;
+        };
+
+        for primitive in mesh.primitives() {
+            if primitive.mode() != gltf::mesh::Mode::Triangles {
+                continue;
+            }
+
+            // Only the embedded binary buffer is available in a `.glb`
+            let reader = primitive.reader(|buffer| match buffer.source() {
+                gltf::buffer::Source::Bin => blob,
+                gltf::buffer::Source::Uri(_) => None,
+            });
+
+            let Some(primitive_positions) = reader.read_positions() else {
+                continue;
+            };
+
+            let first = positions.len() as u32;
+            positions.extend(primitive_positions.map(|p| transform.transform_point3(p.into())));
+            let count = positions.len() as u32 - first;
+
+            let indices: Vec<u32> = match reader.read_indices() {
+                Some(indices) => indices.into_u32().collect(),
+                None => (0..count).collect(),
+            };
+            if indices.iter().any(|&i| i >= count) {
+                return Err(invalid_data("index out of range in the glTF file"));
+            }
+
+            triangles.extend(
+                indices
+                    .chunks_exact(3)
+                    .map(|t| [first + t[0], first + t[1], first + t[2]]),
+            );
+        }
+    }
+
+    Ok((positions, triangles))
+}
+
+/// How a shape is combined with the terrain
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Reflect)]
+pub enum CsgOperation {
+    #[default]
+    Union,
+    Subtraction,
+}
+
+/// A [`MeshSdf`] placed in the world, used as a node of the [`Generator`](super::Generator) and
+/// as a brush to edit the loaded chunks
+#[derive(Debug, Clone)]
+pub struct MeshStamp {
+    pub sdf: Arc<MeshSdf>,
+    pub translation: Vec3,
+    pub scale: f32,
+    pub operation: CsgOperation,
+}
+
+impl MeshStamp {
+    pub fn signed_distance(&self, p: Vec3) -> f32 {
+        self.sdf.sample((p - self.translation) / self.scale) * self.scale
+    }
+
+    /// Combines the signed distance `d` of the terrain at `p` with the stamp
+    pub fn apply(&self, d: f32, p: Vec3) -> f32 {
+        let stamp = self.signed_distance(p);
+        match self.operation {
+            CsgOperation::Union => d.min(stamp),
+            CsgOperation::Subtraction => sdf::subtraction(d, stamp),
+        }
+    }
+
+    /// World space box outside of which the stamp doesn't change the terrain
+    pub fn bounds(&self) -> (Vec3, Vec3) {
+        let (min, max) = self.sdf.bounds();
+        (
+            min * self.scale + self.translation,
+            max * self.scale + self.translation,
+        )
+    }
+}
@@ -0,0 +1,17 @@
+pub mod chunk;
+pub mod chunk_map;
+pub mod collision;
+pub mod culling;
+pub mod debug;
+pub mod editing;
+pub mod export;
+pub mod generation;
+pub mod meshing;
+pub mod priority;
+pub mod scheduling;
+pub mod walker;
+
+/// 2.0 means half the detail
+///
+/// TODO: make it dynamic
+pub const LEVEL_OF_DETAIL: f32 = 1.0;
@@ -1,9 +1,3 @@
-mod chunk;
-mod chunk_map;
-mod debug;
-mod generation;
-mod meshing;
-
 use bevy::{
     pbr::wireframe::WireframePlugin,
     prelude::*,
@@ -18,11 +12,11 @@ use smooth_bevy_cameras::{
     controllers::fps::{FpsCameraBundle, FpsCameraController, FpsCameraPlugin},
     LookTransform, LookTransformPlugin,
 };
-
-/// 2.0 means half the detail
-///
-/// TODO: make it dynamic
-const LEVEL_OF_DETAIL: f32 = 1.0;
+use surface_nets_experiment::{
+    collision::{self, CharacterController},
+    culling, debug, editing, export, generation, meshing,
+    walker::{self, PlanetWalker},
+};
 
 fn main() {
     App::new()
@@ -52,12 +46,24 @@ fn main() {
             bevy_egui::EguiPlugin,
             LookTransformPlugin,
             FpsCameraPlugin::default(),
-            generation::GenerationPlugin,
+            generation::GenerationPlugin { erosion: true },
             meshing::MeshingPlugin,
+            collision::CollisionPlugin,
+            culling::CullingPlugin,
+            walker::WalkerPlugin,
+            editing::EditingPlugin,
+            export::ExportPlugin,
             debug::DebugPlugin,
         ))
         .add_systems(Startup, setup)
-        .add_systems(Update, (camera_focus_origin, toggle_cursor_and_camera))
+        .add_systems(
+            Update,
+            (
+                camera_focus_origin,
+                toggle_cursor_and_camera,
+                toggle_walking,
+            ),
+        )
         .run();
 }
 
@@ -136,3 +142,49 @@ fn toggle_cursor_and_camera(
         camera.enabled = !camera.enabled;
     }
 }
+
+/// Drops a planet walker where the free camera is, or goes back to the free camera
+fn toggle_walking(
+    mut commands: Commands,
+    keys: Res<Input<KeyCode>>,
+    walkers: Query<Entity, With<PlanetWalker>>,
+    mut free_cameras: Query<(&Transform, &mut Camera, &mut FpsCameraController)>,
+) {
+    if !keys.just_pressed(KeyCode::G) {
+        return;
+    }
+
+    let Ok((camera_transform, mut camera, mut controller)) = free_cameras.get_single_mut() else {
+        return;
+    };
+
+    if let Ok(walker) = walkers.get_single() {
+        commands.entity(walker).despawn_recursive();
+        camera.is_active = true;
+        controller.enabled = true;
+        return;
+    }
+
+    let position = camera_transform.translation;
+    let up = position.normalize_or_zero();
+
+    commands
+        .spawn((
+            Name::new("Planet walker"),
+            SpatialBundle::from_transform(
+                Transform::from_translation(position)
+                    .with_rotation(Quat::from_rotation_arc(Vec3::Y, up)),
+            ),
+            CharacterController::default(),
+            PlanetWalker::default(),
+        ))
+        .with_children(|parent| {
+            parent.spawn(Camera3dBundle {
+                transform: Transform::from_translation(Vec3::Y * 0.6),
+                ..default()
+            });
+        });
+
+    camera.is_active = false;
+    controller.enabled = false;
+}
@@ -0,0 +1,39 @@
+use bevy::prelude::*;
+
+use super::mesher::MeshBuffer;
+use crate::{generation::BiomeMap, LEVEL_OF_DETAIL};
+
+/// How the chunk meshes are colored
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Reflect)]
+pub enum ChunkColoring {
+    /// One random color per chunk, to tell them apart
+    #[default]
+    Random,
+    /// Vertex colors blended from the biomes at the world position of the vertices
+    Biomes,
+    /// One color per chunk, from the biome at its center
+    DominantBiome,
+}
+
+impl ChunkColoring {
+    pub const ALL: [Self; 3] = [Self::Random, Self::Biomes, Self::DominantBiome];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Random => "Random per chunk",
+            Self::Biomes => "Biomes",
+            Self::DominantBiome => "Dominant biome per chunk",
+        }
+    }
+}
+
+pub fn compute_biome_colors(buffer: &mut MeshBuffer, chunk_min: IVec3, biomes: &BiomeMap) {
+    buffer.colors = buffer
+        .positions
+        .iter()
+        .map(|&position| {
+            let p = (chunk_min.as_vec3() + Vec3::from(position)) * LEVEL_OF_DETAIL;
+            biomes.color(p).as_linear_rgba_f32()
+        })
+        .collect();
+}
@@ -0,0 +1,107 @@
+use std::cell::RefCell;
+
+use bevy::prelude::*;
+use fast_surface_nets::ndshape::ConstShape;
+
+use super::{
+    mesher::{sample, MeshBuffer, Mesher, PaddedSdf},
+    sharp_features::feature_vertex,
+};
+use crate::chunk::{PaddedChunkShape, PADDED_CHUNK_SIZE};
+
+/// Same topology as surface nets, but every vertex is placed on the sharp features of its cell
+pub struct DualContouring;
+
+impl Mesher for DualContouring {
+    fn mesh_region(&self, padded_sdf: &PaddedSdf, min: UVec3, max: UVec3, buffer: &mut MeshBuffer) {
+        CELL_TO_INDEX.with(|cell_to_index| {
+            mesh_region(
+                padded_sdf,
+                min,
+                max,
+                &mut cell_to_index.borrow_mut(),
+                buffer,
+            );
+        });
+    }
+}
+
+fn mesh_region(
+    padded_sdf: &PaddedSdf,
+    min: UVec3,
+    max: UVec3,
+    cell_to_index: &mut [u32],
+    buffer: &mut MeshBuffer,
+) {
+    let mut surface_cells = Vec::new();
+
+    for z in min.z..=max.z {
+        for y in min.y..=max.y {
+            for x in min.x..=max.x {
+                let cell = UVec3::new(x, y, z);
+
+                if let Some((position, normal)) = feature_vertex(padded_sdf, cell) {
+                    let i = PaddedChunkShape::linearize(cell.to_array()) as usize;
+                    cell_to_index[i] = buffer.positions.len() as u32;
+                    buffer.positions.push(position.to_array());
+                    buffer.normals.push(normal.to_array());
+                    surface_cells.push(cell);
+                }
+            }
+        }
+    }
+
+    // Same ownership rules as `fast_surface_nets`: a quad is emitted for each edge crossing the
+    // surface whose four surrounding cells are in the region, except on the maximum side which
+    // belongs to the next region.
+    for &cell in &surface_cells {
+        for axis in 0..3 {
+            let u = (axis + 1) % 3;
+            let v = (axis + 2) % 3;
+
+            if cell[u] == min[u] || cell[v] == min[v] || cell[axis] == max[axis] {
+                continue;
+            }
+
+            let mut end = cell;
+            end[axis] += 1;
+
+            let d0 = sample(padded_sdf, cell);
+            let d1 = sample(padded_sdf, end);
+            if (d0 < 0.0) == (d1 < 0.0) {
+                continue;
+            }
+
+            let mut u_offset = UVec3::ZERO;
+            u_offset[u] = 1;
+            let mut v_offset = UVec3::ZERO;
+            v_offset[v] = 1;
+
+            let index =
+                |c: UVec3| cell_to_index[PaddedChunkShape::linearize(c.to_array()) as usize];
+            let v0 = index(cell);
+            let v1 = index(cell - u_offset);
+            let v2 = index(cell - v_offset);
+            let v3 = index(cell - u_offset - v_offset);
+
+            // Going v3, v2, v0, v1 is counter-clockwise around the axis
+            if d0 < 0.0 {
+                buffer.indices.extend_from_slice(&[v3, v2, v0, v3, v0, v1]);
+            } else {
+                buffer.indices.extend_from_slice(&[v3, v0, v2, v3, v1, v0]);
+            }
+        }
+    }
+
+    // Cheaper than clearing the whole table before the next call
+    for &cell in &surface_cells {
+        cell_to_index[PaddedChunkShape::linearize(cell.to_array()) as usize] = NULL_VERTEX;
+    }
+}
+
+const NULL_VERTEX: u32 = u32::MAX;
+
+thread_local! {
+    /// Index of the vertex of each cell of the padded chunk, reused by the calls on the same thread
+    static CELL_TO_INDEX: RefCell<Vec<u32>> = RefCell::new(vec![NULL_VERTEX; PADDED_CHUNK_SIZE]);
+}
@@ -0,0 +1,344 @@
+use std::cell::RefCell;
+
+use bevy::{prelude::*, utils::HashMap};
+use fast_surface_nets::ndshape::ConstShape;
+
+use super::mesher::{gradient, sample, MeshBuffer, Mesher, PaddedSdf, CUBE_CORNERS, CUBE_EDGES};
+use crate::chunk::PaddedChunkShape;
+
+pub struct MarchingCubes;
+
+impl Mesher for MarchingCubes {
+    fn mesh_region(&self, padded_sdf: &PaddedSdf, min: UVec3, max: UVec3, buffer: &mut MeshBuffer) {
+        EDGE_VERTICES.with(|edge_vertices| {
+            let edge_vertices = &mut *edge_vertices.borrow_mut();
+            edge_vertices.clear();
+            mesh_region(padded_sdf, min, max, edge_vertices, buffer);
+        });
+    }
+}
+
+fn mesh_region(
+    padded_sdf: &PaddedSdf,
+    min: UVec3,
+    max: UVec3,
+    edge_vertices: &mut HashMap<(u32, usize), u32>,
+    buffer: &mut MeshBuffer,
+) {
+    // A chunk owns the cells whose minimum corner is inside of it, the padding is only read for
+    // the maximum corners of the last layer of cells
+    for z in min.z..max.z {
+        for y in min.y..max.y {
+            for x in min.x..max.x {
+                let cell = UVec3::new(x, y, z);
+
+                let mut distances = [0.0; 8];
+                let mut case = 0;
+                for (i, d) in distances.iter_mut().enumerate() {
+                    *d = sample(padded_sdf, cell + CUBE_CORNERS[i]);
+                    if *d < 0.0 {
+                        case |= 1 << i;
+                    }
+                }
+
+                if case == 0 || case == 0xff {
+                    continue;
+                }
+
+                for &edge in TRIANGLES[case].iter().take_while(|&&e| e >= 0) {
+                    let edge = edge as usize;
+                    let [a, b] = CUBE_EDGES[edge];
+                    let min_corner = cell + CUBE_CORNERS[a];
+                    let key = (PaddedChunkShape::linearize(min_corner.to_array()), edge / 4);
+
+                    let index = *edge_vertices.entry(key).or_insert_with(|| {
+                        let max_corner = cell + CUBE_CORNERS[b];
+                        let t = distances[a] / (distances[a] - distances[b]);
+
+                        let position = min_corner.as_vec3().lerp(max_corner.as_vec3(), t);
+                        let normal = gradient(padded_sdf, min_corner)
+                            .lerp(gradient(padded_sdf, max_corner), t)
+                            .normalize_or_zero();
+
+                        buffer.positions.push(position.to_array());
+                        buffer.normals.push(normal.to_array());
+                        buffer.positions.len() as u32 - 1
+                    });
+
+                    buffer.indices.push(index);
+                }
+            }
+        }
+    }
+}
+
+thread_local! {
+    /// Vertices are shared between the (up to 4) cells around an edge, keyed by the linear index of
+    /// the edge's minimum corner and its axis. The map is reused by the calls on the same thread.
+    static EDGE_VERTICES: RefCell<HashMap<(u32, usize), u32>> = RefCell::default();
+}
+
+/// Triangles (as edge indices, terminated by -1) for each configuration of negative corners.
+///
+/// The table was generated by walking the faces of the cube, ambiguous faces always separate the
+/// negative corners so that adjacent cells agree and the mesh stays watertight. Triangles are
+/// counter-clockwise when seen from the positive side of the field.
+#[rustfmt::skip]
+const TRIANGLES: [[i8; 16]; 256] = [
+    [-1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+    [4, 8, 0, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+    [9, 5, 0, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+    [4, 8, 9, 4, 9, 5, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+    [10, 4, 1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+    [10, 8, 0, 10, 0, 1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+    [10, 4, 1, 9, 5, 0, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+    [10, 8, 9, 10, 9, 5, 10, 5, 1, -1, -1, -1, -1, -1, -1, -1],
+    [5, 11, 1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+    [4, 8, 0, 5, 11, 1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+    [9, 11, 1, 9, 1, 0, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+    [4, 8, 9, 4, 9, 11, 4, 11, 1, -1, -1, -1, -1, -1, -1, -1],
+    [10, 4, 5, 10, 5, 11, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+    [10, 8, 0, 10, 0, 5, 10, 5, 11, -1, -1, -1, -1, -1, -1, -1],
+    [10, 4, 0, 10, 0, 9, 10, 9, 11, -1, -1, -1, -1, -1, -1, -1],
+    [10, 8, 9, 10, 9, 11, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+    [8, 6, 2, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+    [4, 6, 2, 4, 2, 0, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+    [8, 6, 2, 9, 5, 0, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+    [4, 6, 2, 4, 2, 9, 4, 9, 5, -1, -1, -1, -1, -1, -1, -1],
+    [10, 4, 1, 8, 6, 2, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+    [10, 6, 2, 10, 2, 0, 10, 0, 1, -1, -1, -1, -1, -1, -1, -1],
+    [10, 4, 1, 8, 6, 2, 9, 5, 0, -1, -1, -1, -1, -1, -1, -1],
+    [10, 6, 2, 10, 2, 9, 10, 9, 5, 10, 5, 1, -1, -1, -1, -1],
+    [8, 6, 2, 5, 11, 1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+    [4, 6, 2, 4, 2, 0, 5, 11, 1, -1, -1, -1, -1, -1, -1, -1],
+    [8, 6, 2, 9, 11, 1, 9, 1, 0, -1, -1, -1, -1, -1, -1, -1],
+    [4, 6, 2, 4, 2, 9, 4, 9, 11, 4, 11, 1, -1, -1, -1, -1],
+    [10, 4, 5, 10, 5, 11, 8, 6, 2, -1, -1, -1, -1, -1, -1, -1],
+    [10, 6, 2, 10, 2, 0, 10, 0, 5, 10, 5, 11, -1, -1, -1, -1],
+    [10, 4, 0, 10, 0, 9, 10, 9, 11, 8, 6, 2, -1, -1, -1, -1],
+    [10, 6, 2, 10, 2, 9, 10, 9, 11, -1, -1, -1, -1, -1, -1, -1],
+    [7, 9, 2, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+    [4, 8, 0, 7, 9, 2, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+    [7, 5, 0, 7, 0, 2, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+    [4, 8, 2, 4, 2, 7, 4, 7, 5, -1, -1, -1, -1, -1, -1, -1],
+    [10, 4, 1, 7, 9, 2, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+    [10, 8, 0, 10, 0, 1, 7, 9, 2, -1, -1, -1, -1, -1, -1, -1],
+    [10, 4, 1, 7, 5, 0, 7, 0, 2, -1, -1, -1, -1, -1, -1, -1],
+    [10, 8, 2, 10, 2, 7, 10, 7, 5, 10, 5, 1, -1, -1, -1, -1],
+    [5, 11, 1, 7, 9, 2, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+    [4, 8, 0, 5, 11, 1, 7, 9, 2, -1, -1, -1, -1, -1, -1, -1],
+    [7, 11, 1, 7, 1, 0, 7, 0, 2, -1, -1, -1, -1, -1, -1, -1],
+    [4, 8, 2, 4, 2, 7, 4, 7, 11, 4, 11, 1, -1, -1, -1, -1],
+    [10, 4, 5, 10, 5, 11, 7, 9, 2, -1, -1, -1, -1, -1, -1, -1],
+    [10, 8, 0, 10, 0, 5, 10, 5, 11, 7, 9, 2, -1, -1, -1, -1],
+    [10, 4, 0, 10, 0, 2, 10, 2, 7, 10, 7, 11, -1, -1, -1, -1],
+    [10, 8, 2, 10, 2, 7, 10, 7, 11, -1, -1, -1, -1, -1, -1, -1],
+    [8, 6, 7, 8, 7, 9, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+    [4, 6, 7, 4, 7, 9, 4, 9, 0, -1, -1, -1, -1, -1, -1, -1],
+    [8, 6, 7, 8, 7, 5, 8, 5, 0, -1, -1, -1, -1, -1, -1, -1],
+    [4, 6, 7, 4, 7, 5, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+    [10, 4, 1, 8, 6, 7, 8, 7, 9, -1, -1, -1, -1, -1, -1, -1],
+    [10, 6, 7, 10, 7, 9, 10, 9, 0, 10, 0, 1, -1, -1, -1, -1],
+    [10, 4, 1, 8, 6, 7, 8, 7, 5, 8, 5, 0, -1, -1, -1, -1],
+    [10, 6, 7, 10, 7, 5, 10, 5, 1, -1, -1, -1, -1, -1, -1, -1],
+    [8, 6, 7, 8, 7, 9, 5, 11, 1, -1, -1, -1, -1, -1, -1, -1],
+    [4, 6, 7, 4, 7, 9, 4, 9, 0, 5, 11, 1, -1, -1, -1, -1],
+    [8, 6, 7, 8, 7, 11, 8, 11, 1, 8, 1, 0, -1, -1, -1, -1],
+    [4, 6, 7, 4, 7, 11, 4, 11, 1, -1, -1, -1, -1, -1, -1, -1],
+    [10, 4, 5, 10, 5, 11, 8, 6, 7, 8, 7, 9, -1, -1, -1, -1],
+    [10, 6, 7, 10, 7, 9, 10, 9, 0, 10, 0, 5, 10, 5, 11, -1],
+    [10, 4, 0, 10, 0, 8, 10, 8, 6, 10, 6, 7, 10, 7, 11, -1],
+    [10, 6, 7, 10, 7, 11, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+    [6, 10, 3, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+    [4, 8, 0, 6, 10, 3, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+    [6, 10, 3, 9, 5, 0, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+    [4, 8, 9, 4, 9, 5, 6, 10, 3, -1, -1, -1, -1, -1, -1, -1],
+    [6, 4, 1, 6, 1, 3, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+    [6, 8, 0, 6, 0, 1, 6, 1, 3, -1, -1, -1, -1, -1, -1, -1],
+    [6, 4, 1, 6, 1, 3, 9, 5, 0, -1, -1, -1, -1, -1, -1, -1],
+    [6, 8, 9, 6, 9, 5, 6, 5, 1, 6, 1, 3, -1, -1, -1, -1],
+    [6, 10, 3, 5, 11, 1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+    [4, 8, 0, 6, 10, 3, 5, 11, 1, -1, -1, -1, -1, -1, -1, -1],
+    [6, 10, 3, 9, 11, 1, 9, 1, 0, -1, -1, -1, -1, -1, -1, -1],
+    [4, 8, 9, 4, 9, 11, 4, 11, 1, 6, 10, 3, -1, -1, -1, -1],
+    [6, 4, 5, 6, 5, 11, 6, 11, 3, -1, -1, -1, -1, -1, -1, -1],
+    [6, 8, 0, 6, 0, 5, 6, 5, 11, 6, 11, 3, -1, -1, -1, -1],
+    [6, 4, 0, 6, 0, 9, 6, 9, 11, 6, 11, 3, -1, -1, -1, -1],
+    [6, 8, 9, 6, 9, 11, 6, 11, 3, -1, -1, -1, -1, -1, -1, -1],
+    [8, 10, 3, 8, 3, 2, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+    [4, 10, 3, 4, 3, 2, 4, 2, 0, -1, -1, -1, -1, -1, -1, -1],
+    [8, 10, 3, 8, 3, 2, 9, 5, 0, -1, -1, -1, -1, -1, -1, -1],
+    [4, 10, 3, 4, 3, 2, 4, 2, 9, 4, 9, 5, -1, -1, -1, -1],
+    [8, 4, 1, 8, 1, 3, 8, 3, 2, -1, -1, -1, -1, -1, -1, -1],
+    [2, 0, 1, 2, 1, 3, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+    [8, 4, 1, 8, 1, 3, 8, 3, 2, 9, 5, 0, -1, -1, -1, -1],
+    [9, 5, 1, 9, 1, 3, 9, 3, 2, -1, -1, -1, -1, -1, -1, -1],
+    [8, 10, 3, 8, 3, 2, 5, 11, 1, -1, -1, -1, -1, -1, -1, -1],
+    [4, 10, 3, 4, 3, 2, 4, 2, 0, 5, 11, 1, -1, -1, -1, -1],
+    [8, 10, 3, 8, 3, 2, 9, 11, 1, 9, 1, 0, -1, -1, -1, -1],
+    [4, 10, 3, 4, 3, 2, 4, 2, 9, 4, 9, 11, 4, 11, 1, -1],
+    [8, 4, 5, 8, 5, 11, 8, 11, 3, 8, 3, 2, -1, -1, -1, -1],
+    [5, 11, 3, 5, 3, 2, 5, 2, 0, -1, -1, -1, -1, -1, -1, -1],
+    [8, 4, 0, 8, 0, 9, 8, 9, 11, 8, 11, 3, 8, 3, 2, -1],
+    [9, 11, 3, 9, 3, 2, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+    [6, 10, 3, 7, 9, 2, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+    [4, 8, 0, 6, 10, 3, 7, 9, 2, -1, -1, -1, -1, -1, -1, -1],
+    [6, 10, 3, 7, 5, 0, 7, 0, 2, -1, -1, -1, -1, -1, -1, -1],
+    [4, 8, 2, 4, 2, 7, 4, 7, 5, 6, 10, 3, -1, -1, -1, -1],
+    [6, 4, 1, 6, 1, 3, 7, 9, 2, -1, -1, -1, -1, -1, -1, -1],
+    [6, 8, 0, 6, 0, 1, 6, 1, 3, 7, 9, 2, -1, -1, -1, -1],
+    [6, 4, 1, 6, 1, 3, 7, 5, 0, 7, 0, 2, -1, -1, -1, -1],
+    [6, 8, 2, 6, 2, 7, 6, 7, 5, 6, 5, 1, 6, 1, 3, -1],
+    [6, 10, 3, 5, 11, 1, 7, 9, 2, -1, -1, -1, -1, -1, -1, -1],
+    [4, 8, 0, 6, 10, 3, 5, 11, 1, 7, 9, 2, -1, -1, -1, -1],
+    [6, 10, 3, 7, 11, 1, 7, 1, 0, 7, 0, 2, -1, -1, -1, -1],
+    [4, 8, 2, 4, 2, 7, 4, 7, 11, 4, 11, 1, 6, 10, 3, -1],
+    [6, 4, 5, 6, 5, 11, 6, 11, 3, 7, 9, 2, -1, -1, -1, -1],
+    [6, 8, 0, 6, 0, 5, 6, 5, 11, 6, 11, 3, 7, 9, 2, -1],
+    [6, 4, 0, 6, 0, 2, 6, 2, 7, 6, 7, 11, 6, 11, 3, -1],
+    [6, 8, 2, 6, 2, 7, 6, 7, 11, 6, 11, 3, -1, -1, -1, -1],
+    [8, 10, 3, 8, 3, 7, 8, 7, 9, -1, -1, -1, -1, -1, -1, -1],
+    [4, 10, 3, 4, 3, 7, 4, 7, 9, 4, 9, 0, -1, -1, -1, -1],
+    [8, 10, 3, 8, 3, 7, 8, 7, 5, 8, 5, 0, -1, -1, -1, -1],
+    [4, 10, 3, 4, 3, 7, 4, 7, 5, -1, -1, -1, -1, -1, -1, -1],
+    [8, 4, 1, 8, 1, 3, 8, 3, 7, 8, 7, 9, -1, -1, -1, -1],
+    [7, 9, 0, 7, 0, 1, 7, 1, 3, -1, -1, -1, -1, -1, -1, -1],
+    [8, 4, 1, 8, 1, 3, 8, 3, 7, 8, 7, 5, 8, 5, 0, -1],
+    [7, 5, 1, 7, 1, 3, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+    [8, 10, 3, 8, 3, 7, 8, 7, 9, 5, 11, 1, -1, -1, -1, -1],
+    [4, 10, 3, 4, 3, 7, 4, 7, 9, 4, 9, 0, 5, 11, 1, -1],
+    [8, 10, 3, 8, 3, 7, 8, 7, 11, 8, 11, 1, 8, 1, 0, -1],
+    [4, 10, 3, 4, 3, 7, 4, 7, 11, 4, 11, 1, -1, -1, -1, -1],
+    [8, 4, 5, 8, 5, 11, 8, 11, 3, 8, 3, 7, 8, 7, 9, -1],
+    [5, 11, 3, 5, 3, 7, 5, 7, 9, 5, 9, 0, -1, -1, -1, -1],
+    [8, 4, 0, 7, 11, 3, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+    [7, 11, 3, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+    [11, 7, 3, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+    [4, 8, 0, 11, 7, 3, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+    [9, 5, 0, 11, 7, 3, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+    [4, 8, 9, 4, 9, 5, 11, 7, 3, -1, -1, -1, -1, -1, -1, -1],
+    [10, 4, 1, 11, 7, 3, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+    [10, 8, 0, 10, 0, 1, 11, 7, 3, -1, -1, -1, -1, -1, -1, -1],
+    [10, 4, 1, 9, 5, 0, 11, 7, 3, -1, -1, -1, -1, -1, -1, -1],
+    [10, 8, 9, 10, 9, 5, 10, 5, 1, 11, 7, 3, -1, -1, -1, -1],
+    [5, 7, 3, 5, 3, 1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+    [4, 8, 0, 5, 7, 3, 5, 3, 1, -1, -1, -1, -1, -1, -1, -1],
+    [9, 7, 3, 9, 3, 1, 9, 1, 0, -1, -1, -1, -1, -1, -1, -1],
+    [4, 8, 9, 4, 9, 7, 4, 7, 3, 4, 3, 1, -1, -1, -1, -1],
+    [10, 4, 5, 10, 5, 7, 10, 7, 3, -1, -1, -1, -1, -1, -1, -1],
+    [10, 8, 0, 10, 0, 5, 10, 5, 7, 10, 7, 3, -1, -1, -1, -1],
+    [10, 4, 0, 10, 0, 9, 10, 9, 7, 10, 7, 3, -1, -1, -1, -1],
+    [10, 8, 9, 10, 9, 7, 10, 7, 3, -1, -1, -1, -1, -1, -1, -1],
+    [8, 6, 2, 11, 7, 3, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+    [4, 6, 2, 4, 2, 0, 11, 7, 3, -1, -1, -1, -1, -1, -1, -1],
+    [8, 6, 2, 9, 5, 0, 11, 7, 3, -1, -1, -1, -1, -1, -1, -1],
+    [4, 6, 2, 4, 2, 9, 4, 9, 5, 11, 7, 3, -1, -1, -1, -1],
+    [10, 4, 1, 8, 6, 2, 11, 7, 3, -1, -1, -1, -1, -1, -1, -1],
+    [10, 6, 2, 10, 2, 0, 10, 0, 1, 11, 7, 3, -1, -1, -1, -1],
+    [10, 4, 1, 8, 6, 2, 9, 5, 0, 11, 7, 3, -1, -1, -1, -1],
+    [10, 6, 2, 10, 2, 9, 10, 9, 5, 10, 5, 1, 11, 7, 3, -1],
+    [8, 6, 2, 5, 7, 3, 5, 3, 1, -1, -1, -1, -1, -1, -1, -1],
+    [4, 6, 2, 4, 2, 0, 5, 7, 3, 5, 3, 1, -1, -1, -1, -1],
+    [8, 6, 2, 9, 7, 3, 9, 3, 1, 9, 1, 0, -1, -1, -1, -1],
+    [4, 6, 2, 4, 2, 9, 4, 9, 7, 4, 7, 3, 4, 3, 1, -1],
+    [10, 4, 5, 10, 5, 7, 10, 7, 3, 8, 6, 2, -1, -1, -1, -1],
+    [10, 6, 2, 10, 2, 0, 10, 0, 5, 10, 5, 7, 10, 7, 3, -1],
+    [10, 4, 0, 10, 0, 9, 10, 9, 7, 10, 7, 3, 8, 6, 2, -1],
+    [10, 6, 2, 10, 2, 9, 10, 9, 7, 10, 7, 3, -1, -1, -1, -1],
+    [11, 9, 2, 11, 2, 3, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+    [4, 8, 0, 11, 9, 2, 11, 2, 3, -1, -1, -1, -1, -1, -1, -1],
+    [11, 5, 0, 11, 0, 2, 11, 2, 3, -1, -1, -1, -1, -1, -1, -1],
+    [4, 8, 2, 4, 2, 3, 4, 3, 11, 4, 11, 5, -1, -1, -1, -1],
+    [10, 4, 1, 11, 9, 2, 11, 2, 3, -1, -1, -1, -1, -1, -1, -1],
+    [10, 8, 0, 10, 0, 1, 11, 9, 2, 11, 2, 3, -1, -1, -1, -1],
+    [10, 4, 1, 11, 5, 0, 11, 0, 2, 11, 2, 3, -1, -1, -1, -1],
+    [10, 8, 2, 10, 2, 3, 10, 3, 11, 10, 11, 5, 10, 5, 1, -1],
+    [5, 9, 2, 5, 2, 3, 5, 3, 1, -1, -1, -1, -1, -1, -1, -1],
+    [4, 8, 0, 5, 9, 2, 5, 2, 3, 5, 3, 1, -1, -1, -1, -1],
+    [0, 2, 3, 0, 3, 1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+    [4, 8, 2, 4, 2, 3, 4, 3, 1, -1, -1, -1, -1, -1, -1, -1],
+    [10, 4, 5, 10, 5, 9, 10, 9, 2, 10, 2, 3, -1, -1, -1, -1],
+    [10, 8, 0, 10, 0, 5, 10, 5, 9, 10, 9, 2, 10, 2, 3, -1],
+    [10, 4, 0, 10, 0, 2, 10, 2, 3, -1, -1, -1, -1, -1, -1, -1],
+    [10, 8, 2, 10, 2, 3, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+    [8, 6, 3, 8, 3, 11, 8, 11, 9, -1, -1, -1, -1, -1, -1, -1],
+    [4, 6, 3, 4, 3, 11, 4, 11, 9, 4, 9, 0, -1, -1, -1, -1],
+    [8, 6, 3, 8, 3, 11, 8, 11, 5, 8, 5, 0, -1, -1, -1, -1],
+    [4, 6, 3, 4, 3, 11, 4, 11, 5, -1, -1, -1, -1, -1, -1, -1],
+    [10, 4, 1, 8, 6, 3, 8, 3, 11, 8, 11, 9, -1, -1, -1, -1],
+    [10, 6, 3, 10, 3, 11, 10, 11, 9, 10, 9, 0, 10, 0, 1, -1],
+    [10, 4, 1, 8, 6, 3, 8, 3, 11, 8, 11, 5, 8, 5, 0, -1],
+    [10, 6, 3, 10, 3, 11, 10, 11, 5, 10, 5, 1, -1, -1, -1, -1],
+    [8, 6, 3, 8, 3, 1, 8, 1, 5, 8, 5, 9, -1, -1, -1, -1],
+    [4, 6, 3, 4, 3, 1, 4, 1, 5, 4, 5, 9, 4, 9, 0, -1],
+    [8, 6, 3, 8, 3, 1, 8, 1, 0, -1, -1, -1, -1, -1, -1, -1],
+    [4, 6, 3, 4, 3, 1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+    [10, 4, 5, 10, 5, 9, 10, 9, 8, 10, 8, 6, 10, 6, 3, -1],
+    [10, 6, 3, 5, 9, 0, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+    [10, 4, 0, 10, 0, 8, 10, 8, 6, 10, 6, 3, -1, -1, -1, -1],
+    [10, 6, 3, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+    [6, 10, 11, 6, 11, 7, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+    [4, 8, 0, 6, 10, 11, 6, 11, 7, -1, -1, -1, -1, -1, -1, -1],
+    [6, 10, 11, 6, 11, 7, 9, 5, 0, -1, -1, -1, -1, -1, -1, -1],
+    [4, 8, 9, 4, 9, 5, 6, 10, 11, 6, 11, 7, -1, -1, -1, -1],
+    [6, 4, 1, 6, 1, 11, 6, 11, 7, -1, -1, -1, -1, -1, -1, -1],
+    [6, 8, 0, 6, 0, 1, 6, 1, 11, 6, 11, 7, -1, -1, -1, -1],
+    [6, 4, 1, 6, 1, 11, 6, 11, 7, 9, 5, 0, -1, -1, -1, -1],
+    [6, 8, 9, 6, 9, 5, 6, 5, 1, 6, 1, 11, 6, 11, 7, -1],
+    [6, 10, 1, 6, 1, 5, 6, 5, 7, -1, -1, -1, -1, -1, -1, -1],
+    [4, 8, 0, 6, 10, 1, 6, 1, 5, 6, 5, 7, -1, -1, -1, -1],
+    [6, 10, 1, 6, 1, 0, 6, 0, 9, 6, 9, 7, -1, -1, -1, -1],
+    [4, 8, 9, 4, 9, 7, 4, 7, 6, 4, 6, 10, 4, 10, 1, -1],
+    [6, 4, 5, 6, 5, 7, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+    [6, 8, 0, 6, 0, 5, 6, 5, 7, -1, -1, -1, -1, -1, -1, -1],
+    [6, 4, 0, 6, 0, 9, 6, 9, 7, -1, -1, -1, -1, -1, -1, -1],
+    [6, 8, 9, 6, 9, 7, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+    [8, 10, 11, 8, 11, 7, 8, 7, 2, -1, -1, -1, -1, -1, -1, -1],
+    [4, 10, 11, 4, 11, 7, 4, 7, 2, 4, 2, 0, -1, -1, -1, -1],
+    [8, 10, 11, 8, 11, 7, 8, 7, 2, 9, 5, 0, -1, -1, -1, -1],
+    [4, 10, 11, 4, 11, 7, 4, 7, 2, 4, 2, 9, 4, 9, 5, -1],
+    [8, 4, 1, 8, 1, 11, 8, 11, 7, 8, 7, 2, -1, -1, -1, -1],
+    [11, 7, 2, 11, 2, 0, 11, 0, 1, -1, -1, -1, -1, -1, -1, -1],
+    [8, 4, 1, 8, 1, 11, 8, 11, 7, 8, 7, 2, 9, 5, 0, -1],
+    [9, 5, 1, 9, 1, 11, 9, 11, 7, 9, 7, 2, -1, -1, -1, -1],
+    [8, 10, 1, 8, 1, 5, 8, 5, 7, 8, 7, 2, -1, -1, -1, -1],
+    [4, 10, 1, 4, 1, 5, 4, 5, 7, 4, 7, 2, 4, 2, 0, -1],
+    [8, 10, 1, 8, 1, 0, 8, 0, 9, 8, 9, 7, 8, 7, 2, -1],
+    [4, 10, 1, 9, 7, 2, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+    [8, 4, 5, 8, 5, 7, 8, 7, 2, -1, -1, -1, -1, -1, -1, -1],
+    [5, 7, 2, 5, 2, 0, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+    [8, 4, 0, 8, 0, 9, 8, 9, 7, 8, 7, 2, -1, -1, -1, -1],
+    [9, 7, 2, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+    [6, 10, 11, 6, 11, 9, 6, 9, 2, -1, -1, -1, -1, -1, -1, -1],
+    [4, 8, 0, 6, 10, 11, 6, 11, 9, 6, 9, 2, -1, -1, -1, -1],
+    [6, 10, 11, 6, 11, 5, 6, 5, 0, 6, 0, 2, -1, -1, -1, -1],
+    [4, 8, 2, 4, 2, 6, 4, 6, 10, 4, 10, 11, 4, 11, 5, -1],
+    [6, 4, 1, 6, 1, 11, 6, 11, 9, 6, 9, 2, -1, -1, -1, -1],
+    [6, 8, 0, 6, 0, 1, 6, 1, 11, 6, 11, 9, 6, 9, 2, -1],
+    [6, 4, 1, 6, 1, 11, 6, 11, 5, 6, 5, 0, 6, 0, 2, -1],
+    [6, 8, 2, 11, 5, 1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+    [6, 10, 1, 6, 1, 5, 6, 5, 9, 6, 9, 2, -1, -1, -1, -1],
+    [4, 8, 0, 6, 10, 1, 6, 1, 5, 6, 5, 9, 6, 9, 2, -1],
+    [6, 10, 1, 6, 1, 0, 6, 0, 2, -1, -1, -1, -1, -1, -1, -1],
+    [4, 8, 2, 4, 2, 6, 4, 6, 10, 4, 10, 1, -1, -1, -1, -1],
+    [6, 4, 5, 6, 5, 9, 6, 9, 2, -1, -1, -1, -1, -1, -1, -1],
+    [6, 8, 0, 6, 0, 5, 6, 5, 9, 6, 9, 2, -1, -1, -1, -1],
+    [6, 4, 0, 6, 0, 2, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+    [6, 8, 2, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+    [8, 10, 11, 8, 11, 9, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+    [4, 10, 11, 4, 11, 9, 4, 9, 0, -1, -1, -1, -1, -1, -1, -1],
+    [8, 10, 11, 8, 11, 5, 8, 5, 0, -1, -1, -1, -1, -1, -1, -1],
+    [4, 10, 11, 4, 11, 5, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+    [8, 4, 1, 8, 1, 11, 8, 11, 9, -1, -1, -1, -1, -1, -1, -1],
+    [11, 9, 0, 11, 0, 1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+    [8, 4, 1, 8, 1, 11, 8, 11, 5, 8, 5, 0, -1, -1, -1, -1],
+    [11, 5, 1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+    [8, 10, 1, 8, 1, 5, 8, 5, 9, -1, -1, -1, -1, -1, -1, -1],
+    [4, 10, 1, 4, 1, 5, 4, 5, 9, 4, 9, 0, -1, -1, -1, -1],
+    [8, 10, 1, 8, 1, 0, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+    [4, 10, 1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+    [8, 4, 5, 8, 5, 9, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+    [5, 9, 0, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+    [8, 4, 0, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+    [-1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+];
@@ -0,0 +1,177 @@
+use std::sync::Arc;
+
+use bevy::{
+    prelude::*,
+    render::{
+        mesh::{Indices, VertexAttributeValues},
+        render_resource::PrimitiveTopology,
+    },
+};
+use fast_surface_nets::ndshape::ConstShape;
+
+use super::{
+    colors::ChunkColoring, dual_contouring::DualContouring, marching_cubes::MarchingCubes,
+    normals::NormalsMode, simplification::SimplificationSettings, surface_nets::SurfaceNets,
+};
+use crate::chunk::{PaddedChunkShape, Sd8, CHUNK_SIDE, PADDED_CHUNK_SIDE, PADDED_CHUNK_SIZE};
+
+pub type PaddedSdf = [Sd8; PADDED_CHUNK_SIZE];
+
+/// Vertices and triangles produced by a [`Mesher`], expressed in the local space of the padded chunk
+#[derive(Debug, Default)]
+pub struct MeshBuffer {
+    pub positions: Vec<[f32; 3]>,
+    pub normals: Vec<[f32; 3]>,
+    /// Optional linear RGBA vertex colors, filled after the post-processing steps
+    pub colors: Vec<[f32; 4]>,
+    pub indices: Vec<u32>,
+}
+
+impl MeshBuffer {
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+
+    /// Appends the vertices and triangles of another buffer, without welding the shared vertices
+    pub fn append(&mut self, other: &MeshBuffer) {
+        let offset = self.positions.len() as u32;
+        self.positions.extend_from_slice(&other.positions);
+        self.normals.extend_from_slice(&other.normals);
+        self.colors.extend_from_slice(&other.colors);
+        self.indices
+            .extend(other.indices.iter().map(|&index| index + offset));
+    }
+
+    pub fn into_mesh(self) -> Mesh {
+        let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+        mesh.insert_attribute(
+            Mesh::ATTRIBUTE_POSITION,
+            VertexAttributeValues::Float32x3(self.positions),
+        );
+        mesh.insert_attribute(
+            Mesh::ATTRIBUTE_NORMAL,
+            VertexAttributeValues::Float32x3(self.normals),
+        );
+        if !self.colors.is_empty() {
+            mesh.insert_attribute(
+                Mesh::ATTRIBUTE_COLOR,
+                VertexAttributeValues::Float32x4(self.colors),
+            );
+        }
+        mesh.set_indices(Some(Indices::U32(self.indices)));
+        mesh
+    }
+}
+
+/// Extracts the isosurface of a padded chunk.
+///
+/// Implementations must only emit the triangles "owned" by the chunk, the padding is only there to
+/// give access to the neighboring samples, so that adjacent chunks line up without overlapping.
+pub trait Mesher: Send + Sync {
+    /// Emits the triangles owned by the cells of the chunk in `min..max`. Meshing adjacent regions
+    /// emits the same triangles as meshing their union, but the vertices on their shared faces are
+    /// duplicated.
+    fn mesh_region(&self, padded_sdf: &PaddedSdf, min: UVec3, max: UVec3, buffer: &mut MeshBuffer);
+
+    fn mesh(&self, padded_sdf: &PaddedSdf, buffer: &mut MeshBuffer) {
+        self.mesh_region(padded_sdf, UVec3::ZERO, UVec3::splat(CHUNK_SIDE), buffer);
+    }
+}
+
+/// Settings of the meshing tasks, changing them remeshes every loaded chunk
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Reflect)]
+pub struct MeshingSettings {
+    pub mesher: MesherKind,
+    /// Keeps the creases and corners crisp, only used by surface nets since dual contouring always
+    /// preserves them
+    pub sharp_features: bool,
+    pub normals: NormalsMode,
+    pub simplify: bool,
+    pub simplification: SimplificationSettings,
+    /// Builds a `ChunkCollider` along with the mesh
+    pub colliders: bool,
+    pub coloring: ChunkColoring,
+}
+
+impl MeshingSettings {
+    pub fn mesher(&self) -> Arc<dyn Mesher> {
+        match self.mesher {
+            MesherKind::SurfaceNets => Arc::new(SurfaceNets {
+                sharp_features: self.sharp_features,
+            }),
+            MesherKind::MarchingCubes => Arc::new(MarchingCubes),
+            MesherKind::DualContouring => Arc::new(DualContouring),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Reflect)]
+pub enum MesherKind {
+    #[default]
+    SurfaceNets,
+    MarchingCubes,
+    DualContouring,
+}
+
+impl MesherKind {
+    pub const ALL: [Self; 3] = [Self::SurfaceNets, Self::MarchingCubes, Self::DualContouring];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::SurfaceNets => "Surface Nets",
+            Self::MarchingCubes => "Marching Cubes",
+            Self::DualContouring => "Dual Contouring",
+        }
+    }
+}
+
+/// Offset of each corner of a cell, the bits of the corner index are its x, y and z coordinates
+pub const CUBE_CORNERS: [UVec3; 8] = [
+    UVec3::new(0, 0, 0),
+    UVec3::new(1, 0, 0),
+    UVec3::new(0, 1, 0),
+    UVec3::new(1, 1, 0),
+    UVec3::new(0, 0, 1),
+    UVec3::new(1, 0, 1),
+    UVec3::new(0, 1, 1),
+    UVec3::new(1, 1, 1),
+];
+
+/// Corners joined by each edge, grouped by axis: 0..4 are along X, 4..8 along Y and 8..12 along Z
+pub const CUBE_EDGES: [[usize; 2]; 12] = [
+    [0, 1],
+    [2, 3],
+    [4, 5],
+    [6, 7],
+    [0, 2],
+    [1, 3],
+    [4, 6],
+    [5, 7],
+    [0, 4],
+    [1, 5],
+    [2, 6],
+    [3, 7],
+];
+
+#[inline]
+pub fn sample(padded_sdf: &PaddedSdf, p: UVec3) -> f32 {
+    padded_sdf[PaddedChunkShape::linearize(p.to_array()) as usize].into()
+}
+
+/// Gradient of the distance field at a sample point, using central differences where possible and
+/// one-sided differences on the borders of the padded chunk.
+pub fn gradient(padded_sdf: &PaddedSdf, p: UVec3) -> Vec3 {
+    let mut gradient = Vec3::ZERO;
+
+    for axis in 0..3 {
+        let mut lo = p;
+        let mut hi = p;
+        lo[axis] = lo[axis].saturating_sub(1);
+        hi[axis] = (hi[axis] + 1).min(PADDED_CHUNK_SIDE - 1);
+
+        gradient[axis] =
+            (sample(padded_sdf, hi) - sample(padded_sdf, lo)) / (hi[axis] - lo[axis]) as f32;
+    }
+
+    gradient
+}
@@ -0,0 +1,497 @@
+mod colors;
+mod dual_contouring;
+mod marching_cubes;
+mod mesher;
+mod normals;
+mod qef;
+mod sharp_features;
+mod simplification;
+mod surface_nets;
+
+use std::{cell::RefCell, sync::Arc, time::Instant};
+
+use bevy::prelude::*;
+use crossbeam_queue::SegQueue;
+use float_ord::FloatOrd;
+
+use rand::Rng;
+use tracing::Instrument;
+
+use crate::{
+    chunk::{ChunkKey, Sd8, CHUNK_SHAPE, PADDED_CHUNK_SIZE},
+    chunk_map::{
+        ChunkMap, ChunkMeshed, ChunkNeighborhood, ChunkState, CurrentChunks, DirtyChunks,
+        SubBlockMask, SUB_BLOCK_COUNT, SUB_BLOCK_SIDE,
+    },
+    collision::ChunkCollider,
+    culling::FaceConnectivity,
+    generation::{ChunkGenerator, WorldGenerator, WorldSeed},
+    priority::{chunk_priority, ChunkViewer, PrioritySettings, SurfaceHint},
+    scheduling::{SchedulingPlugin, VoxelTaskPools, VoxelTaskStats},
+    LEVEL_OF_DETAIL,
+};
+
+pub use colors::ChunkColoring;
+pub use mesher::{MeshBuffer, Mesher, MesherKind, MeshingSettings, PaddedSdf};
+pub use normals::NormalsMode;
+pub use qef::Qef;
+pub use sharp_features::feature_vertex;
+pub use simplification::{is_near_boundary, simplify, SimplificationSettings, DETAIL_LEVELS};
+
+pub struct MeshingPlugin;
+
+impl Plugin for MeshingPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<SchedulingPlugin>() {
+            app.add_plugins(SchedulingPlugin);
+        }
+
+        app.init_resource::<MeshingResults>()
+            .init_resource::<MeshingSettings>()
+            .register_type::<MeshingSettings>()
+            .add_event::<ChunkMeshed>()
+            .add_systems(
+                Update,
+                (
+                    remesh_all_chunks.run_if(resource_changed::<MeshingSettings>()),
+                    update_detail_levels
+                        .run_if(|s: Res<MeshingSettings>| s.simplify)
+                        .before(spawn_chunk_meshing_tasks),
+                    spawn_chunk_meshing_tasks,
+                    handle_chunk_meshing_results
+                        .run_if(|r: Res<MeshingResults>| !r.is_empty())
+                        .after(spawn_chunk_meshing_tasks),
+                ),
+            );
+    }
+}
+
+#[derive(Resource, Deref, Default)]
+pub struct MeshingResults(
+    Arc<
+        SegQueue<(
+            Entity,
+            ChunkKey,
+            Option<(Mesh, ChunkMeshStats)>,
+            Option<ChunkCollider>,
+            Option<ChunkMeshBlocks>,
+            FaceConnectivity,
+        )>,
+    >,
+);
+
+/// Triangle counts of a chunk's mesh, before and after simplification
+#[derive(Component, Debug, Default, Clone, Copy)]
+pub struct ChunkMeshStats {
+    pub generated_triangles: usize,
+    pub final_triangles: usize,
+    /// Simplification level the mesh was built with, see [`SimplificationSettings::detail_level`]
+    pub detail_level: u8,
+}
+
+/// Mesh buffers of the sub-blocks of an edited chunk, so that the next edits only remesh the
+/// sub-blocks they touch. The buffers are post-processed, except for the simplification which
+/// needs the whole mesh and disables this.
+#[derive(Component, Debug, Clone)]
+pub struct ChunkMeshBlocks(Vec<Arc<MeshBuffer>>);
+
+impl Default for ChunkMeshBlocks {
+    fn default() -> Self {
+        Self(vec![Arc::default(); SUB_BLOCK_COUNT])
+    }
+}
+
+impl ChunkMeshBlocks {
+    /// Concatenation of the buffers of every sub-block
+    pub fn merged(&self) -> MeshBuffer {
+        let mut buffer = MeshBuffer::default();
+        for block in &self.0 {
+            buffer.append(block);
+        }
+        buffer
+    }
+}
+
+fn spawn_chunk_meshing_tasks(
+    pools: Res<VoxelTaskPools>,
+    mut stats: ResMut<VoxelTaskStats>,
+    chunk_map: Res<ChunkMap>,
+    mut dirty_chunks: ResMut<DirtyChunks>,
+    current_chunks: Res<CurrentChunks>,
+    mut chunks: Query<(&mut ChunkState, Option<&ChunkMeshBlocks>)>,
+    meshing_results: Res<MeshingResults>,
+    meshing_settings: Res<MeshingSettings>,
+    generator: Res<WorldGenerator>,
+    viewer: Res<ChunkViewer>,
+    priority_settings: Res<PrioritySettings>,
+) {
+    let mesher = meshing_settings.mesher();
+    let settings = *meshing_settings;
+
+    let mut processed_chunks = Vec::with_capacity(dirty_chunks.len());
+
+    // Stays dirty until its neighbors are generated
+    let mut ready_chunks: Vec<_> = dirty_chunks
+        .iter()
+        .filter(|&(&key, _)| chunk_map.is_ready_to_mesh(key, &current_chunks))
+        .map(|(&key, &dirty_blocks)| {
+            let uniform = chunk_map.is_neighborhood_uniform(key);
+            let surface = SurfaceHint(!uniform);
+            let priority = chunk_priority(key, surface, &viewer, &priority_settings);
+            (FloatOrd(priority), key, dirty_blocks, uniform)
+        })
+        .collect();
+    ready_chunks.sort_unstable_by_key(|&(priority, ..)| priority);
+
+    let mut available = stats.meshing.available();
+    for (_, key, dirty_blocks, uniform) in ready_chunks {
+        // The other chunks stay dirty until tasks finish
+        if available == 0 && !uniform {
+            continue;
+        }
+
+        let entity = current_chunks.get_entity(key).unwrap();
+        let (mut state, blocks) = chunks.get_mut(entity).unwrap();
+
+        // Stays dirty until the current mesh arrives, the results of two tasks could arrive out of
+        // order, and the sub-blocks are patched into the buffers of the previous mesh
+        if *state == ChunkState::Meshing {
+            continue;
+        }
+        if let Err(error) = state.transition(ChunkState::Meshing) {
+            warn!("{error} for chunk {key:?}");
+            processed_chunks.push(key);
+            continue;
+        }
+
+        // Only air or only matter, the mesh is empty so there is no task to wait for. It's still
+        // counted as one since its result is handled like theirs, later in the frame.
+        if uniform {
+            let connectivity = chunk_map.face_connectivity(key);
+            stats.meshing.start_task();
+            meshing_results.push((entity, key, None, None, None, connectivity));
+            processed_chunks.push(key);
+            continue;
+        }
+        available -= 1;
+
+        // The first edit of a chunk meshes all of its sub-blocks, the whole chunk is meshed at
+        // once otherwise
+        let blocks = (!dirty_blocks.is_all() && !settings.simplify).then(|| match blocks {
+            Some(blocks) => (blocks.clone(), dirty_blocks),
+            None => (ChunkMeshBlocks::default(), SubBlockMask::ALL),
+        });
+
+        let detail_level = if settings.simplify {
+            let distance = viewer_distance(key, &viewer);
+            settings.simplification.detail_level(distance)
+        } else {
+            0
+        };
+
+        let chunk_map = ChunkMap::clone(&chunk_map);
+        let meshing_results = Arc::clone(&meshing_results);
+        let mesher = Arc::clone(&mesher);
+        let generator = Arc::clone(&generator);
+        let latencies = stats.meshing.start_task();
+        let spawned = Instant::now();
+        pools
+            .meshing()
+            .spawn(
+                async move {
+                    // The voxels are read from the shared store and copied off the main thread
+                    let (result, blocks) = mesh_neighborhood(
+                        &chunk_map.neighborhood(key),
+                        mesher.as_ref(),
+                        generator.as_ref(),
+                        &settings,
+                        detail_level,
+                        blocks,
+                    );
+                    let collider = result
+                        .as_ref()
+                        .filter(|_| settings.colliders)
+                        .and_then(|(mesh, _)| ChunkCollider::from_mesh(mesh));
+                    let connectivity = chunk_map.face_connectivity(key);
+                    latencies.record(spawned.elapsed());
+                    meshing_results.push((entity, key, result, collider, blocks, connectivity));
+                }
+                .instrument(trace_span!("chunk_meshing_task")),
+            )
+            .detach();
+
+        processed_chunks.push(key);
+    }
+
+    processed_chunks.into_iter().for_each(|k| {
+        dirty_chunks.remove(&k);
+    });
+    stats.meshing.queued = dirty_chunks.len();
+}
+
+thread_local! {
+    /// Padded chunk the meshing tasks copy the voxels into, reused by the tasks on the same thread
+    static PADDED_SDF: RefCell<Box<PaddedSdf>> =
+        RefCell::new(Box::new([Sd8::MAX; PADDED_CHUNK_SIZE]));
+}
+
+/// Body of the meshing tasks, the sub-blocks are remeshed if `blocks` is given
+fn mesh_neighborhood(
+    neighborhood: &ChunkNeighborhood,
+    mesher: &dyn Mesher,
+    generator: &dyn ChunkGenerator,
+    settings: &MeshingSettings,
+    detail_level: u8,
+    blocks: Option<(ChunkMeshBlocks, SubBlockMask)>,
+) -> (Option<(Mesh, ChunkMeshStats)>, Option<ChunkMeshBlocks>) {
+    let key = neighborhood.key();
+
+    PADDED_SDF.with(|padded_sdf| {
+        let padded_sdf = &mut *padded_sdf.borrow_mut();
+        neighborhood.copy_to(padded_sdf);
+
+        match blocks {
+            Some((mut blocks, dirty)) => {
+                let result = remesh_chunk_blocks(
+                    key,
+                    padded_sdf,
+                    mesher,
+                    generator,
+                    settings,
+                    &mut blocks,
+                    dirty,
+                );
+                (result, Some(blocks))
+            }
+            None => (
+                mesh_chunk(key, padded_sdf, mesher, generator, settings, detail_level),
+                None,
+            ),
+        }
+    })
+}
+
+/// Meshes a padded chunk and applies the post-processing steps enabled in the settings, the
+/// generator is only sampled for the normals and colors that need it. The mesh is simplified
+/// according to `detail_level` if the settings enable it, 0 keeps the full mesh.
+pub fn mesh_chunk(
+    key: ChunkKey,
+    padded_sdf: &PaddedSdf,
+    mesher: &dyn Mesher,
+    generator: &dyn ChunkGenerator,
+    settings: &MeshingSettings,
+    detail_level: u8,
+) -> Option<(Mesh, ChunkMeshStats)> {
+    let mut buffer = MeshBuffer::default();
+    mesher.mesh(padded_sdf, &mut buffer);
+
+    if buffer.is_empty() {
+        return None;
+    }
+
+    let generated_triangles = buffer.indices.len() / 3;
+    let simplification = settings.simplification;
+    if settings.simplify {
+        simplify(
+            &mut buffer,
+            simplification.level_ratio(detail_level),
+            simplification.max_error,
+        );
+    }
+    let stats = ChunkMeshStats {
+        generated_triangles,
+        final_triangles: buffer.indices.len() / 3,
+        detail_level,
+    };
+
+    post_process_vertices(&mut buffer, key, padded_sdf, generator, settings);
+
+    Some((into_chunk_mesh(buffer, settings), stats))
+}
+
+/// Remeshes the `dirty` sub-blocks of a chunk into `blocks`, the mesh is made of the buffers of
+/// every sub-block. The settings must be the ones the other sub-blocks were meshed with, and the
+/// simplification is ignored.
+pub fn remesh_chunk_blocks(
+    key: ChunkKey,
+    padded_sdf: &PaddedSdf,
+    mesher: &dyn Mesher,
+    generator: &dyn ChunkGenerator,
+    settings: &MeshingSettings,
+    blocks: &mut ChunkMeshBlocks,
+    dirty: SubBlockMask,
+) -> Option<(Mesh, ChunkMeshStats)> {
+    for index in dirty.iter() {
+        let min = SubBlockMask::block_min(index);
+        let mut buffer = MeshBuffer::default();
+        mesher.mesh_region(padded_sdf, min, min + SUB_BLOCK_SIDE, &mut buffer);
+        post_process_vertices(&mut buffer, key, padded_sdf, generator, settings);
+        blocks.0[index] = Arc::new(buffer);
+    }
+
+    let buffer = blocks.merged();
+    if buffer.is_empty() {
+        return None;
+    }
+
+    let triangles = buffer.indices.len() / 3;
+    let stats = ChunkMeshStats {
+        generated_triangles: triangles,
+        final_triangles: triangles,
+        detail_level: 0,
+    };
+
+    Some((into_chunk_mesh(buffer, settings), stats))
+}
+
+/// Normals and colors, computed for each vertex independently
+fn post_process_vertices(
+    buffer: &mut MeshBuffer,
+    key: ChunkKey,
+    padded_sdf: &PaddedSdf,
+    generator: &dyn ChunkGenerator,
+    settings: &MeshingSettings,
+) {
+    match settings.normals {
+        NormalsMode::Mesher | NormalsMode::Flat => {}
+        NormalsMode::Trilinear => normals::compute_trilinear_normals(buffer, padded_sdf),
+        NormalsMode::Generator => {
+            normals::compute_generator_normals(buffer, key.min_point(), generator)
+        }
+    }
+
+    if let (ChunkColoring::Biomes, Some(biomes)) = (settings.coloring, generator.biomes()) {
+        colors::compute_biome_colors(buffer, key.min_point(), biomes);
+    }
+}
+
+fn into_chunk_mesh(buffer: MeshBuffer, settings: &MeshingSettings) -> Mesh {
+    let mut mesh = buffer.into_mesh();
+
+    if settings.normals == NormalsMode::Flat {
+        mesh.duplicate_vertices();
+        mesh.compute_flat_normals();
+    }
+
+    mesh
+}
+
+fn remesh_all_chunks(chunk_map: Res<ChunkMap>, mut dirty_chunks: ResMut<DirtyChunks>) {
+    dirty_chunks.extend(chunk_map.storage.keys());
+}
+
+/// Distance between the viewer and the center of a chunk, in world units
+fn viewer_distance(key: ChunkKey, viewer: &ChunkViewer) -> f32 {
+    let center = key.min_point().as_vec3() + CHUNK_SHAPE.as_vec3() / 2.0;
+    (center * LEVEL_OF_DETAIL).distance(viewer.position)
+}
+
+/// Remeshes the simplified chunks whose distance to the viewer moved them to another detail level
+fn update_detail_levels(
+    meshing_settings: Res<MeshingSettings>,
+    viewer: Res<ChunkViewer>,
+    mut dirty_chunks: ResMut<DirtyChunks>,
+    chunks: Query<(&ChunkKey, &ChunkState, &ChunkMeshStats)>,
+) {
+    let settings = meshing_settings.simplification;
+
+    for (&key, state, stats) in chunks.iter() {
+        // Chunks without triangles have nothing to simplify
+        if *state != ChunkState::Meshed || stats.final_triangles == 0 {
+            continue;
+        }
+        if settings.detail_level(viewer_distance(key, &viewer)) != stats.detail_level {
+            dirty_chunks.insert(key);
+        }
+    }
+}
+
+fn handle_chunk_meshing_results(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    meshing_results: Res<MeshingResults>,
+    meshing_settings: Res<MeshingSettings>,
+    generator: Res<WorldGenerator>,
+    seed: Res<WorldSeed>,
+    mut states: Query<&mut ChunkState>,
+    mut stats: ResMut<VoxelTaskStats>,
+    mut meshed: EventWriter<ChunkMeshed>,
+) {
+    while let Some((entity, key, result, collider, blocks, connectivity)) = meshing_results.pop() {
+        stats.meshing.finish_task();
+
+        // The chunk was unloaded while it was meshing
+        let Ok(mut state) = states.get_mut(entity) else {
+            continue;
+        };
+        if *state == ChunkState::Unloading {
+            continue;
+        }
+        if let Err(error) = state.transition(ChunkState::Meshed) {
+            warn!("{error} for chunk {key:?}");
+        }
+        meshed.send(ChunkMeshed { key, entity });
+
+        // Keep the collider in sync with the mesh when the chunk is remeshed
+        match collider {
+            Some(collider) => commands.entity(entity).insert(collider),
+            None => commands.entity(entity).remove::<ChunkCollider>(),
+        };
+        match blocks {
+            Some(blocks) => commands.entity(entity).insert(blocks),
+            None => commands.entity(entity).remove::<ChunkMeshBlocks>(),
+        };
+        commands.entity(entity).insert(connectivity);
+
+        // Chunks without surface are still reported, a remeshed chunk may have lost its surface
+        let Some((mesh, stats)) = result else {
+            commands
+                .entity(entity)
+                .remove::<Handle<Mesh>>()
+                .insert(ChunkMeshStats::default());
+            continue;
+        };
+
+        let mesh = meshes.add(mesh);
+        let material = {
+            let center = key.min_point().as_vec3() + CHUNK_SHAPE.as_vec3() / 2.0;
+            let dominant_biome = generator
+                .biomes()
+                .map(|biomes| biomes.dominant(center * LEVEL_OF_DETAIL));
+
+            let color = match (meshing_settings.coloring, dominant_biome) {
+                // Multiplied by the vertex colors
+                (ChunkColoring::Biomes, Some(_)) => Color::WHITE,
+                (ChunkColoring::DominantBiome, Some(biome)) => biome.color(),
+                // Also used when the generator has no biomes
+                _ => {
+                    let mut rng = seed.chunk_rng(key);
+                    Color::rgb(
+                        rng.gen_range(0.0..=1.0), //0.168 ,
+                        rng.gen_range(0.0..=1.0), //0.133 ,
+                        rng.gen_range(0.0..=1.0), //0.102 ,
+                    )
+                }
+            };
+            let mut m = StandardMaterial::from(color);
+            m.perceptual_roughness = 0.6;
+            m.metallic = 0.2;
+            materials.add(m)
+        };
+
+        let chunk_min = key.0 * CHUNK_SHAPE;
+        let transform = Transform::from_translation(chunk_min.as_vec3() * LEVEL_OF_DETAIL)
+            .with_scale(Vec3::splat(LEVEL_OF_DETAIL));
+
+        commands.entity(entity).insert((
+            PbrBundle {
+                mesh,
+                material,
+                transform,
+                ..Default::default()
+            },
+            stats,
+        ));
+    }
+}
@@ -0,0 +1,65 @@
+use bevy::prelude::*;
+
+use super::mesher::{gradient, MeshBuffer, PaddedSdf, CUBE_CORNERS};
+use crate::{chunk::PADDED_CHUNK_SIDE, generation::ChunkGenerator, LEVEL_OF_DETAIL};
+
+/// Source of the vertex normals of the chunk meshes
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Reflect)]
+pub enum NormalsMode {
+    /// Normals computed by the mesher, usually from the quantized field which causes banding
+    #[default]
+    Mesher,
+    /// Central differences of the stored field, trilinearly interpolated at the vertex positions.
+    /// Smoother across the cells than the mesher's normals, but just as quantized
+    Trilinear,
+    /// Gradient of the generator's signed distance at the world position of the vertices
+    Generator,
+    /// One normal per triangle, for a faceted look
+    Flat,
+}
+
+impl NormalsMode {
+    pub const ALL: [Self; 4] = [Self::Mesher, Self::Trilinear, Self::Generator, Self::Flat];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Mesher => "Mesher",
+            Self::Trilinear => "Trilinear gradient",
+            Self::Generator => "Generator gradient",
+            Self::Flat => "Flat",
+        }
+    }
+}
+
+pub fn compute_trilinear_normals(buffer: &mut MeshBuffer, padded_sdf: &PaddedSdf) {
+    for (normal, &position) in buffer.normals.iter_mut().zip(buffer.positions.iter()) {
+        let p = Vec3::from(position);
+        let cell = p
+            .floor()
+            .as_uvec3()
+            .min(UVec3::splat(PADDED_CHUNK_SIDE - 2));
+        let t = p - cell.as_vec3();
+
+        let interpolated: Vec3 = CUBE_CORNERS
+            .iter()
+            .map(|&corner| {
+                let c = corner.as_vec3();
+                let weights = (Vec3::ONE - c) + (2.0 * c - Vec3::ONE) * t;
+                gradient(padded_sdf, cell + corner) * weights.x * weights.y * weights.z
+            })
+            .sum();
+
+        *normal = interpolated.normalize_or_zero().to_array();
+    }
+}
+
+pub fn compute_generator_normals(
+    buffer: &mut MeshBuffer,
+    chunk_min: IVec3,
+    generator: &dyn ChunkGenerator,
+) {
+    for (normal, &position) in buffer.normals.iter_mut().zip(buffer.positions.iter()) {
+        let p = (chunk_min.as_vec3() + Vec3::from(position)) * LEVEL_OF_DETAIL;
+        *normal = generator.gradient(p).normalize_or_zero().to_array();
+    }
+}
@@ -0,0 +1,48 @@
+use bevy::prelude::*;
+
+/// Quadratic error function accumulating the tangent planes of a cell's edge crossings.
+///
+/// Its minimizer is the point closest to all the planes, which lies on the corner or the crease
+/// they describe instead of being averaged out like the mass point.
+#[derive(Debug, Clone, Copy)]
+pub struct Qef {
+    ata: Mat3,
+    atb: Vec3,
+    point_sum: Vec3,
+    count: u32,
+}
+
+impl Default for Qef {
+    fn default() -> Self {
+        // `Mat3::default()` is the identity
+        Self {
+            ata: Mat3::ZERO,
+            atb: Vec3::ZERO,
+            point_sum: Vec3::ZERO,
+            count: 0,
+        }
+    }
+}
+
+impl Qef {
+    pub fn add(&mut self, point: Vec3, normal: Vec3) {
+        self.ata += Mat3::from_cols(normal * normal.x, normal * normal.y, normal * normal.z);
+        self.atb += normal * normal.dot(point);
+        self.point_sum += point;
+        self.count += 1;
+    }
+
+    pub fn mass_point(&self) -> Vec3 {
+        self.point_sum / self.count.max(1) as f32
+    }
+
+    /// Minimizes the error, `bias` pulls the solution towards the mass point which keeps the
+    /// system well conditioned when the planes are (nearly) parallel, i.e. on flat surfaces.
+    pub fn solve(&self, bias: f32) -> Vec3 {
+        let mass_point = self.mass_point();
+        let a = self.ata + Mat3::from_diagonal(Vec3::splat(bias));
+        let b = self.atb - self.ata * mass_point;
+
+        mass_point + a.inverse() * b
+    }
+}
@@ -0,0 +1,98 @@
+use bevy::prelude::*;
+
+use super::{
+    mesher::{sample, PaddedSdf, CUBE_CORNERS, CUBE_EDGES},
+    qef::Qef,
+};
+use crate::chunk::PADDED_CHUNK_SIDE;
+
+/// Bias towards the mass point used when solving the QEF of a cell
+const QEF_BIAS: f32 = 0.05;
+
+/// Difference between the forward and backward slopes above which an edge crossing is considered
+/// to be next to a crease
+const CREASE_THRESHOLD: f32 = 0.25;
+
+/// Places the vertex of a cell crossing the surface at the minimizer of the QEF built from the
+/// crossings of its edges and the normals of the field at these crossings.
+///
+/// Returns the position and the averaged normal, or `None` if the cell doesn't cross the surface.
+pub fn feature_vertex(padded_sdf: &PaddedSdf, cell: UVec3) -> Option<(Vec3, Vec3)> {
+    let corners = CUBE_CORNERS.map(|c| cell + c);
+    let distances = corners.map(|c| sample(padded_sdf, c));
+
+    let negative_count = distances.iter().filter(|&&d| d < 0.0).count();
+    if negative_count == 0 || negative_count == 8 {
+        return None;
+    }
+
+    let mut qef = Qef::default();
+    let mut normal_sum = Vec3::ZERO;
+
+    for (edge, [a, b]) in CUBE_EDGES.into_iter().enumerate() {
+        let (da, db) = (distances[a], distances[b]);
+        if (da < 0.0) == (db < 0.0) {
+            continue;
+        }
+
+        let t = da / (da - db);
+        let point = corners[a].as_vec3().lerp(corners[b].as_vec3(), t);
+        let normal = crossing_normal(padded_sdf, corners[a], edge / 4, t).normalize_or_zero();
+
+        qef.add(point, normal);
+        normal_sum += normal;
+    }
+
+    // Planes built from the quantized field can intersect far away from the surface, in which case
+    // the mass point is a safer choice than clamping the solution to the cell
+    let cell_min = cell.as_vec3();
+    let solution = qef.solve(QEF_BIAS);
+    let position = if solution.cmpge(cell_min).all() && solution.cmple(cell_min + Vec3::ONE).all() {
+        solution
+    } else {
+        qef.mass_point()
+    };
+
+    Some((position, normal_sum.normalize_or_zero()))
+}
+
+/// Normal of the field where it crosses the edge starting at `start` along `axis`.
+///
+/// Central differences across a crease average the normals of the two faces meeting there, which
+/// is what rounds off the corners. When the forward and backward slopes disagree, the smoother one
+/// is used instead so that each crossing keeps the normal of its own face.
+fn crossing_normal(padded_sdf: &PaddedSdf, start: UVec3, axis: usize, t: f32) -> Vec3 {
+    let mut edge = UVec3::ZERO;
+    edge[axis] = 1;
+
+    let value_along_edge =
+        |p: UVec3| sample(padded_sdf, p) * (1.0 - t) + sample(padded_sdf, p + edge) * t;
+    let crossing_value = value_along_edge(start);
+
+    let mut normal = Vec3::ZERO;
+    normal[axis] = sample(padded_sdf, start + edge) - sample(padded_sdf, start);
+
+    for u in [(axis + 1) % 3, (axis + 2) % 3] {
+        let mut step = UVec3::ZERO;
+        step[u] = 1;
+
+        let forward = (start[u] + 1 < PADDED_CHUNK_SIDE)
+            .then(|| value_along_edge(start + step) - crossing_value);
+        let backward = (start[u] > 0).then(|| crossing_value - value_along_edge(start - step));
+
+        normal[u] = match (forward, backward) {
+            (Some(f), Some(b)) if (f - b).abs() > CREASE_THRESHOLD => {
+                if f.abs() < b.abs() {
+                    f
+                } else {
+                    b
+                }
+            }
+            (Some(f), Some(b)) => 0.5 * (f + b),
+            (Some(d), None) | (None, Some(d)) => d,
+            (None, None) => 0.0,
+        };
+    }
+
+    normal
+}
@@ -0,0 +1,417 @@
+use std::{cmp::Ordering, collections::BinaryHeap};
+
+use bevy::{
+    math::{DMat3, DVec3},
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+
+use super::mesher::MeshBuffer;
+use crate::chunk::CHUNK_SIDE;
+
+/// Parameters of the quadric error decimation, the chunks are simplified more the farther they are
+/// from the viewer
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+pub struct SimplificationSettings {
+    /// Maximum quadric error (squared distance to the original planes) of a collapse
+    pub max_error: f32,
+    /// Fraction of the original triangle count kept by the chunks at `far_distance` and beyond
+    pub target_ratio: f32,
+    /// Chunks closer to the viewer keep all their triangles
+    pub near_distance: f32,
+    /// Distance at which the chunks reach the target ratio
+    pub far_distance: f32,
+}
+
+impl Default for SimplificationSettings {
+    fn default() -> Self {
+        Self {
+            max_error: 0.01,
+            target_ratio: 0.25,
+            near_distance: 64.0,
+            far_distance: 384.0,
+        }
+    }
+}
+
+/// Steps between the full mesh and the target ratio, a chunk is only remeshed when its distance
+/// moves it to another step
+pub const DETAIL_LEVELS: u8 = 4;
+
+impl SimplificationSettings {
+    /// Detail level of a chunk at `distance` from the viewer, from 0 for the full mesh to
+    /// [`DETAIL_LEVELS`] for the target ratio
+    pub fn detail_level(&self, distance: f32) -> u8 {
+        let range = (self.far_distance - self.near_distance).max(f32::EPSILON);
+        let t = ((distance - self.near_distance) / range).clamp(0.0, 1.0);
+        (t * DETAIL_LEVELS as f32).round() as u8
+    }
+
+    /// Fraction of the original triangle count kept at a detail level
+    pub fn level_ratio(&self, level: u8) -> f32 {
+        let t = level.min(DETAIL_LEVELS) as f32 / DETAIL_LEVELS as f32;
+        1.0 + (self.target_ratio - 1.0) * t
+    }
+}
+
+/// Decimates the mesh by repeatedly collapsing the edge with the lowest quadric error, until
+/// `target_ratio` of its triangles are left or the next collapse costs more than `max_error`.
+///
+/// Vertices close to the chunk boundary or on an open edge are locked, the neighboring chunks
+/// share them so they must not move for the seams to line up.
+pub fn simplify(buffer: &mut MeshBuffer, target_ratio: f32, max_error: f32) {
+    if target_ratio >= 1.0 {
+        return;
+    }
+
+    let vertex_count = buffer.positions.len();
+    let mut positions: Vec<DVec3> = buffer
+        .positions
+        .iter()
+        .map(|&p| Vec3::from(p).as_dvec3())
+        .collect();
+    let mut triangles: Vec<[u32; 3]> = buffer
+        .indices
+        .chunks_exact(3)
+        .map(|t| [t[0], t[1], t[2]])
+        .collect();
+
+    let target_count = (triangles.len() as f32 * target_ratio) as usize;
+    let max_error = max_error as f64;
+
+    let mut quadrics = vec![Quadric::default(); vertex_count];
+    let mut vertex_triangles = vec![Vec::new(); vertex_count];
+    for (i, t) in triangles.iter().enumerate() {
+        let quadric = Quadric::from_triangle(t.map(|v| positions[v as usize]));
+        for &v in t {
+            quadrics[v as usize] += quadric;
+            vertex_triangles[v as usize].push(i as u32);
+        }
+    }
+
+    let mut locked: Vec<bool> = positions.iter().map(|&p| is_near_boundary(p)).collect();
+    for (a, b) in open_edges(&triangles) {
+        locked[a as usize] = true;
+        locked[b as usize] = true;
+    }
+
+    let mut removed_triangles = vec![false; triangles.len()];
+    let mut removed_vertices = vec![false; vertex_count];
+    let mut versions = vec![0u32; vertex_count];
+    let mut live_count = triangles.len();
+
+    let mut heap = BinaryHeap::new();
+    for t in &triangles {
+        for (a, b) in [(t[0], t[1]), (t[1], t[2]), (t[2], t[0])] {
+            if a < b {
+                push_collapse(&mut heap, a, b, &positions, &quadrics, &locked, &versions);
+            }
+        }
+    }
+
+    while live_count > target_count {
+        let Some(collapse) = heap.pop() else {
+            break;
+        };
+
+        if collapse.error > max_error {
+            break;
+        }
+
+        let (a, b) = (collapse.a as usize, collapse.b as usize);
+        if removed_vertices[a]
+            || removed_vertices[b]
+            || versions[a] != collapse.versions.0
+            || versions[b] != collapse.versions.1
+        {
+            continue;
+        }
+
+        // The vertex kept is the locked one (if any), so that it doesn't move
+        let (keep, remove) = if locked[b] { (b, a) } else { (a, b) };
+
+        if !satisfies_link_condition(&triangles, &removed_triangles, &vertex_triangles, a, b)
+            || flips_triangle(
+                &triangles,
+                &removed_triangles,
+                &vertex_triangles,
+                &positions,
+                remove,
+                keep,
+                collapse.target,
+            )
+            || flips_triangle(
+                &triangles,
+                &removed_triangles,
+                &vertex_triangles,
+                &positions,
+                keep,
+                remove,
+                collapse.target,
+            )
+        {
+            continue;
+        }
+
+        positions[keep] = collapse.target;
+        let removed_quadric = quadrics[remove];
+        quadrics[keep] += removed_quadric;
+        removed_vertices[remove] = true;
+
+        for t in std::mem::take(&mut vertex_triangles[remove]) {
+            if removed_triangles[t as usize] {
+                continue;
+            }
+
+            let triangle = &mut triangles[t as usize];
+            if triangle.contains(&(keep as u32)) {
+                removed_triangles[t as usize] = true;
+                live_count -= 1;
+            } else {
+                triangle.iter_mut().for_each(|v| {
+                    if *v == remove as u32 {
+                        *v = keep as u32;
+                    }
+                });
+                vertex_triangles[keep].push(t);
+            }
+        }
+        vertex_triangles[keep].retain(|&t| !removed_triangles[t as usize]);
+
+        versions[keep] += 1;
+        let neighbors: Vec<u32> = vertex_triangles[keep]
+            .iter()
+            .flat_map(|&t| triangles[t as usize])
+            .filter(|&v| v as usize != keep)
+            .collect();
+        for n in neighbors {
+            let (a, b) = (keep as u32, n);
+            push_collapse(&mut heap, a, b, &positions, &quadrics, &locked, &versions);
+        }
+    }
+
+    // Compact the remaining vertices and triangles
+    let mut remap = vec![u32::MAX; vertex_count];
+    let mut new_positions = Vec::new();
+    let mut new_normals = Vec::new();
+    for v in (0..vertex_count).filter(|&v| !removed_vertices[v]) {
+        remap[v] = new_positions.len() as u32;
+        new_positions.push(positions[v].as_vec3().to_array());
+        new_normals.push(buffer.normals[v]);
+    }
+
+    buffer.indices = triangles
+        .iter()
+        .zip(removed_triangles)
+        .filter(|(_, removed)| !removed)
+        .flat_map(|(t, _)| t.map(|v| remap[v as usize]))
+        .collect();
+    buffer.positions = new_positions;
+    buffer.normals = new_normals;
+}
+
+/// Vertices within a cell of the chunk faces are shared with the neighboring chunks' meshes
+pub fn is_near_boundary(p: DVec3) -> bool {
+    p.min_element() <= 1.0 || p.max_element() >= CHUNK_SIDE as f64
+}
+
+fn open_edges(triangles: &[[u32; 3]]) -> impl Iterator<Item = (u32, u32)> {
+    let mut edges = HashMap::<(u32, u32), u32>::default();
+    for t in triangles {
+        for (a, b) in [(t[0], t[1]), (t[1], t[2]), (t[2], t[0])] {
+            *edges.entry((a.min(b), a.max(b))).or_default() += 1;
+        }
+    }
+
+    edges
+        .into_iter()
+        .filter(|&(_, count)| count == 1)
+        .map(|(edge, _)| edge)
+}
+
+/// Collapsing an edge whose endpoints share more neighbors than the vertices opposite to it would
+/// create non-manifold geometry
+fn satisfies_link_condition(
+    triangles: &[[u32; 3]],
+    removed_triangles: &[bool],
+    vertex_triangles: &[Vec<u32>],
+    a: usize,
+    b: usize,
+) -> bool {
+    let neighbors = |v: usize| -> HashSet<u32> {
+        vertex_triangles[v]
+            .iter()
+            .filter(|&&t| !removed_triangles[t as usize])
+            .flat_map(|&t| triangles[t as usize])
+            .filter(|&n| n as usize != v)
+            .collect()
+    };
+
+    let edge_triangle_count = vertex_triangles[a]
+        .iter()
+        .filter(|&&t| !removed_triangles[t as usize])
+        .filter(|&&t| triangles[t as usize].contains(&(b as u32)))
+        .count();
+
+    neighbors(a).intersection(&neighbors(b)).count() == edge_triangle_count
+}
+
+/// Whether moving `moved` to `target` flips one of its triangles that doesn't contain `other`
+fn flips_triangle(
+    triangles: &[[u32; 3]],
+    removed_triangles: &[bool],
+    vertex_triangles: &[Vec<u32>],
+    positions: &[DVec3],
+    moved: usize,
+    other: usize,
+    target: DVec3,
+) -> bool {
+    vertex_triangles[moved]
+        .iter()
+        .filter(|&&t| !removed_triangles[t as usize])
+        .map(|&t| triangles[t as usize])
+        .filter(|t| !t.contains(&(other as u32)))
+        .any(|t| {
+            let before = t.map(|v| positions[v as usize]);
+            let after = t.map(|v| {
+                if v as usize == moved {
+                    target
+                } else {
+                    positions[v as usize]
+                }
+            });
+
+            let normal_before = (before[1] - before[0]).cross(before[2] - before[0]);
+            let normal_after = (after[1] - after[0]).cross(after[2] - after[0]);
+            normal_before.dot(normal_after) <= 0.0
+        })
+}
+
+fn push_collapse(
+    heap: &mut BinaryHeap<Collapse>,
+    a: u32,
+    b: u32,
+    positions: &[DVec3],
+    quadrics: &[Quadric],
+    locked: &[bool],
+    versions: &[u32],
+) {
+    let (ua, ub) = (a as usize, b as usize);
+    let target = match (locked[ua], locked[ub]) {
+        (true, true) => return,
+        (true, false) => positions[ua],
+        (false, true) => positions[ub],
+        (false, false) => {
+            let quadric = quadrics[ua] + quadrics[ub];
+            let midpoint = 0.5 * (positions[ua] + positions[ub]);
+            let edge_length = positions[ua].distance(positions[ub]);
+
+            // Nearly degenerate quadrics can have their minimizer far away from the edge
+            quadric
+                .minimizer()
+                .filter(|p| p.distance(midpoint) <= edge_length)
+                .unwrap_or_else(|| {
+                    [positions[ua], positions[ub], midpoint]
+                        .into_iter()
+                        .min_by(|p, q| quadric.error(*p).total_cmp(&quadric.error(*q)))
+                        .unwrap()
+                })
+        }
+    };
+
+    let error = (quadrics[ua] + quadrics[ub]).error(target);
+    heap.push(Collapse {
+        error,
+        a,
+        b,
+        target,
+        versions: (versions[ua], versions[ub]),
+    });
+}
+
+struct Collapse {
+    error: f64,
+    a: u32,
+    b: u32,
+    target: DVec3,
+    versions: (u32, u32),
+}
+
+impl PartialEq for Collapse {
+    fn eq(&self, other: &Self) -> bool {
+        self.error == other.error
+    }
+}
+
+impl Eq for Collapse {}
+
+impl PartialOrd for Collapse {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Collapse {
+    /// Reversed so that the `BinaryHeap` pops the cheapest collapse first
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.error.total_cmp(&self.error)
+    }
+}
+
+/// Sum of the squared distances to a set of planes, stored as the symmetric matrix `A`, the vector
+/// `b` and the constant `c` of `xᵀAx + 2bᵀx + c`
+#[derive(Debug, Clone, Copy)]
+struct Quadric {
+    a: DMat3,
+    b: DVec3,
+    c: f64,
+}
+
+impl Default for Quadric {
+    fn default() -> Self {
+        Self {
+            a: DMat3::ZERO,
+            b: DVec3::ZERO,
+            c: 0.0,
+        }
+    }
+}
+
+impl Quadric {
+    fn from_triangle([p0, p1, p2]: [DVec3; 3]) -> Self {
+        let normal = (p1 - p0).cross(p2 - p0).normalize_or_zero();
+        let d = -normal.dot(p0);
+
+        Self {
+            a: DMat3::from_cols(normal * normal.x, normal * normal.y, normal * normal.z),
+            b: normal * d,
+            c: d * d,
+        }
+    }
+
+    fn error(&self, p: DVec3) -> f64 {
+        (p.dot(self.a * p) + 2.0 * self.b.dot(p) + self.c).max(0.0)
+    }
+
+    fn minimizer(&self) -> Option<DVec3> {
+        (self.a.determinant().abs() > 1e-9).then(|| -(self.a.inverse() * self.b))
+    }
+}
+
+impl std::ops::Add for Quadric {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self {
+            a: self.a + rhs.a,
+            b: self.b + rhs.b,
+            c: self.c + rhs.c,
+        }
+    }
+}
+
+impl std::ops::AddAssign for Quadric {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
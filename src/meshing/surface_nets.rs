@@ -0,0 +1,56 @@
+use std::{cell::RefCell, mem};
+
+use bevy::prelude::*;
+use fast_surface_nets::{surface_nets, SurfaceNetsBuffer};
+
+use super::{
+    mesher::{MeshBuffer, Mesher, PaddedSdf},
+    sharp_features::feature_vertex,
+};
+use crate::chunk::PaddedChunkShape;
+
+pub struct SurfaceNets {
+    /// Moves the vertices onto the creases and corners of the field instead of the centroid of the
+    /// edge crossings, which rounds them off
+    pub sharp_features: bool,
+}
+
+impl Mesher for SurfaceNets {
+    fn mesh_region(&self, padded_sdf: &PaddedSdf, min: UVec3, max: UVec3, buffer: &mut MeshBuffer) {
+        SN_BUFFER.with(|sn_buffer| {
+            let sn_buffer = &mut *sn_buffer.borrow_mut();
+
+            // The quads on the minimum faces of the sampled region are skipped, as well as the ones
+            // crossing its maximum faces, so the last cells of the region reach one sample past `max`
+            surface_nets(
+                padded_sdf,
+                &PaddedChunkShape {},
+                min.to_array(),
+                (max + 1).to_array(),
+                sn_buffer,
+            );
+
+            if self.sharp_features {
+                for (position, &cell) in sn_buffer
+                    .positions
+                    .iter_mut()
+                    .zip(sn_buffer.surface_points.iter())
+                {
+                    if let Some((feature, _)) = feature_vertex(padded_sdf, UVec3::from(cell)) {
+                        *position = feature.to_array();
+                    }
+                }
+            }
+
+            // The vertices are moved to the mesh, only the lookup tables are reused
+            buffer.positions = mem::take(&mut sn_buffer.positions);
+            buffer.normals = mem::take(&mut sn_buffer.normals);
+            buffer.indices = mem::take(&mut sn_buffer.indices);
+        });
+    }
+}
+
+thread_local! {
+    /// Reused by the calls on the same thread, it holds an index per sample of the padded chunk
+    static SN_BUFFER: RefCell<SurfaceNetsBuffer> = RefCell::default();
+}
@@ -0,0 +1,108 @@
+use bevy::{
+    math::Affine3A,
+    prelude::*,
+    render::primitives::{Aabb, Frustum},
+};
+
+use crate::{
+    chunk::{ChunkKey, CHUNK_SHAPE},
+    generation::ChunkGenerator,
+    LEVEL_OF_DETAIL,
+};
+
+/// Where the chunks are seen from, the active camera or the origin when there is none
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct ChunkViewer {
+    pub position: Vec3,
+    pub forward: Vec3,
+    pub frustum: Option<Frustum>,
+}
+
+impl Default for ChunkViewer {
+    fn default() -> Self {
+        Self {
+            position: Vec3::ZERO,
+            forward: Vec3::NEG_Z,
+            frustum: None,
+        }
+    }
+}
+
+/// Factors applied to the distance of the chunks, the chunks with the lowest priority are
+/// generated and meshed first
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Reflect)]
+pub struct PrioritySettings {
+    /// Chunks right behind the viewer, the factor goes down to 1 in front of it
+    pub behind: f32,
+    /// Chunks outside the frustum of the camera
+    pub outside_frustum: f32,
+    /// Chunks the generator doesn't expect to contain surface, see [`SurfaceHint`]
+    pub no_surface: f32,
+}
+
+impl Default for PrioritySettings {
+    fn default() -> Self {
+        Self {
+            behind: 2.0,
+            outside_frustum: 4.0,
+            no_surface: 3.0,
+        }
+    }
+}
+
+/// Whether the generator expects the chunk to contain surface, computed when it's queued
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Deref)]
+pub struct SurfaceHint(pub bool);
+
+impl SurfaceHint {
+    pub fn new(key: ChunkKey, generator: &dyn ChunkGenerator) -> Self {
+        let min = key.min_point().as_vec3() * LEVEL_OF_DETAIL;
+        let max = (key.min_point() + CHUNK_SHAPE).as_vec3() * LEVEL_OF_DETAIL;
+        let (lowest, highest) = generator.distance_bounds(min, max);
+        Self(lowest <= 0.0 && highest >= 0.0)
+    }
+}
+
+/// Priority of a chunk, its distance to the viewer scaled by the factors of the settings
+pub fn chunk_priority(
+    key: ChunkKey,
+    surface: SurfaceHint,
+    viewer: &ChunkViewer,
+    settings: &PrioritySettings,
+) -> f32 {
+    let min = key.min_point().as_vec3() * LEVEL_OF_DETAIL;
+    let max = (key.min_point() + CHUNK_SHAPE).as_vec3() * LEVEL_OF_DETAIL;
+    let offset = (min + max) / 2.0 - viewer.position;
+
+    // 0 in front of the viewer, 1 right behind
+    let behind = (1.0 - offset.normalize_or_zero().dot(viewer.forward)) / 2.0;
+    let mut priority = offset.length() * (1.0 + (settings.behind - 1.0) * behind);
+
+    if let Some(frustum) = &viewer.frustum {
+        let aabb = Aabb::from_min_max(min, max);
+        if !frustum.intersects_obb(&aabb, &Affine3A::IDENTITY, false, false) {
+            priority *= settings.outside_frustum;
+        }
+    }
+    if !*surface {
+        priority *= settings.no_surface;
+    }
+
+    priority
+}
+
+/// Follows the active camera, the chunks are prioritized again every frame
+pub(crate) fn update_chunk_viewer(
+    mut viewer: ResMut<ChunkViewer>,
+    cameras: Query<(&Camera, &GlobalTransform, &Frustum)>,
+) {
+    let Some((_, transform, frustum)) = cameras.iter().find(|(camera, ..)| camera.is_active) else {
+        return;
+    };
+
+    *viewer = ChunkViewer {
+        position: transform.translation(),
+        forward: transform.forward(),
+        frustum: Some(*frustum),
+    };
+}
@@ -0,0 +1,337 @@
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use bevy::{
+    diagnostic::{Diagnostic, DiagnosticId, Diagnostics, DiagnosticsStore},
+    prelude::*,
+    tasks::{AsyncComputeTaskPool, TaskPool, TaskPoolBuilder},
+};
+
+use crate::priority::{update_chunk_viewer, ChunkViewer, PrioritySettings};
+
+/// Task pools, task budgets and chunk priorities of the generation and meshing stages, added by
+/// the plugins of both stages
+pub struct SchedulingPlugin;
+
+impl Plugin for SchedulingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<VoxelThreadingConfig>()
+            .register_type::<VoxelThreadingConfig>()
+            .init_resource::<VoxelTaskPools>()
+            .init_resource::<VoxelTaskStats>()
+            .init_resource::<DiagnosticsStore>()
+            .init_resource::<ChunkViewer>()
+            .init_resource::<PrioritySettings>()
+            .register_type::<PrioritySettings>()
+            .add_systems(
+                PreUpdate,
+                (
+                    (rebuild_task_pools, update_task_budgets).chain(),
+                    update_chunk_viewer,
+                ),
+            )
+            .add_systems(PostUpdate, measure_task_stats);
+
+        let mut diagnostics = app.world.resource_mut::<DiagnosticsStore>();
+        for (id, name, suffix) in [
+            (GENERATION_QUEUE, "generation_queue", ""),
+            (MESHING_QUEUE, "meshing_queue", ""),
+            (GENERATION_TASKS, "generation_tasks", ""),
+            (MESHING_TASKS, "meshing_tasks", ""),
+            (GENERATION_LATENCY, "generation_latency", "ms"),
+            (MESHING_LATENCY, "meshing_latency", "ms"),
+        ] {
+            diagnostics.add(Diagnostic::new(id, name, 20).with_suffix(suffix));
+        }
+    }
+}
+
+/// Chunks waiting to be generated
+pub const GENERATION_QUEUE: DiagnosticId =
+    DiagnosticId::from_u128(0x3c1e_5a4f_8d2b_4b61_9a0e_7f52_c6d1_0001);
+/// Dirty chunks waiting to be meshed
+pub const MESHING_QUEUE: DiagnosticId =
+    DiagnosticId::from_u128(0x3c1e_5a4f_8d2b_4b61_9a0e_7f52_c6d1_0002);
+pub const GENERATION_TASKS: DiagnosticId =
+    DiagnosticId::from_u128(0x3c1e_5a4f_8d2b_4b61_9a0e_7f52_c6d1_0003);
+pub const MESHING_TASKS: DiagnosticId =
+    DiagnosticId::from_u128(0x3c1e_5a4f_8d2b_4b61_9a0e_7f52_c6d1_0004);
+/// Time between the spawn of a generation task and the end of its work
+pub const GENERATION_LATENCY: DiagnosticId =
+    DiagnosticId::from_u128(0x3c1e_5a4f_8d2b_4b61_9a0e_7f52_c6d1_0005);
+/// Time between the spawn of a meshing task and the end of its work
+pub const MESHING_LATENCY: DiagnosticId =
+    DiagnosticId::from_u128(0x3c1e_5a4f_8d2b_4b61_9a0e_7f52_c6d1_0006);
+
+/// Threads of the generation and meshing tasks, the task pools are rebuilt when it changes
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+pub struct VoxelThreadingConfig {
+    /// Threads of the generation pool, or the generation share of the shared pool
+    pub generation_threads: usize,
+    /// Threads of the meshing pool, or the meshing share of the shared pool
+    pub meshing_threads: usize,
+    /// Runs the tasks on Bevy's `AsyncComputeTaskPool` instead of pools of their own
+    pub use_async_compute_pool: bool,
+    pub scheduling: Scheduling,
+    /// Tasks a stage may have in flight for each of its threads, so that the chunks keep their
+    /// priority order instead of all waiting in the task pool
+    pub tasks_per_thread: usize,
+}
+
+impl Default for VoxelThreadingConfig {
+    fn default() -> Self {
+        // Leave half of the cores to Bevy's own pools, but never less than the two threads each
+        // stage used to have
+        let threads = std::thread::available_parallelism().map_or(4, |n| n.get() / 2);
+
+        Self {
+            generation_threads: (threads / 2).max(2),
+            meshing_threads: (threads - threads / 2).max(2),
+            use_async_compute_pool: false,
+            scheduling: Scheduling::default(),
+            tasks_per_thread: 2,
+        }
+    }
+}
+
+impl VoxelThreadingConfig {
+    /// Tasks the generation and meshing stages may have in flight, given the length of their
+    /// queues
+    pub fn task_budgets(&self, generation_queue: usize, meshing_queue: usize) -> (usize, usize) {
+        let generation = self.generation_threads.max(1) * self.tasks_per_thread.max(1);
+        let meshing = self.meshing_threads.max(1) * self.tasks_per_thread.max(1);
+
+        match self.scheduling {
+            Scheduling::Fixed => (generation, meshing),
+            Scheduling::Adaptive => {
+                let total = generation + meshing;
+                match (generation_queue, meshing_queue) {
+                    (0, 0) => (generation, meshing),
+                    (_, 0) => (total, 0),
+                    (0, _) => (0, total),
+                    (g, m) => {
+                        let share = (total as f64 * g as f64 / (g + m) as f64).round() as usize;
+                        let generation = share.clamp(1, total - 1);
+                        (generation, total - generation)
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// How the task budget is split between the stages
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Reflect)]
+pub enum Scheduling {
+    /// Each stage has a pool and a budget of its own
+    #[default]
+    Fixed,
+    /// The stages share one pool and split the budget in proportion to their queue lengths
+    Adaptive,
+}
+
+impl Scheduling {
+    pub const ALL: [Self; 2] = [Self::Fixed, Self::Adaptive];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Fixed => "Fixed",
+            Self::Adaptive => "Adaptive",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum PoolHandle {
+    Owned(Arc<TaskPool>),
+    AsyncCompute,
+}
+
+impl PoolHandle {
+    fn get(&self) -> &TaskPool {
+        match self {
+            Self::Owned(pool) => pool,
+            Self::AsyncCompute => AsyncComputeTaskPool::get(),
+        }
+    }
+}
+
+/// Task pools of the generation and meshing stages, which may be the same pool
+#[derive(Resource, Debug, Clone)]
+pub struct VoxelTaskPools {
+    generation: PoolHandle,
+    meshing: PoolHandle,
+    /// Config the pools were built with
+    config: VoxelThreadingConfig,
+    /// Pools replaced while they still had tasks, dropping a pool would cancel them
+    retired: Vec<PoolHandle>,
+}
+
+impl FromWorld for VoxelTaskPools {
+    fn from_world(world: &mut World) -> Self {
+        let config = *world.get_resource_or_insert_with(VoxelThreadingConfig::default);
+        Self::new(config)
+    }
+}
+
+impl VoxelTaskPools {
+    pub fn new(config: VoxelThreadingConfig) -> Self {
+        let pool = |name: &str, threads: usize| {
+            let threads = threads.max(1);
+            PoolHandle::Owned(Arc::new(
+                TaskPoolBuilder::default()
+                    .num_threads(threads)
+                    .thread_name(format!("{name} Task Pool ({threads} threads)"))
+                    .build(),
+            ))
+        };
+
+        let (generation, meshing) = if config.use_async_compute_pool {
+            (PoolHandle::AsyncCompute, PoolHandle::AsyncCompute)
+        } else {
+            match config.scheduling {
+                Scheduling::Fixed => (
+                    pool("Generation", config.generation_threads),
+                    pool("Meshing", config.meshing_threads),
+                ),
+                Scheduling::Adaptive => {
+                    let shared = pool("Voxel", config.generation_threads + config.meshing_threads);
+                    (shared.clone(), shared)
+                }
+            }
+        };
+
+        Self {
+            generation,
+            meshing,
+            config,
+            retired: Vec::new(),
+        }
+    }
+
+    /// Whether the pools built for `config` differ from these ones, the tasks per thread only
+    /// change the budgets
+    fn needs_rebuild(&self, config: &VoxelThreadingConfig) -> bool {
+        let pools = |c: &VoxelThreadingConfig| {
+            (
+                c.generation_threads,
+                c.meshing_threads,
+                c.use_async_compute_pool,
+                c.scheduling,
+            )
+        };
+        pools(&self.config) != pools(config)
+    }
+
+    pub fn generation(&self) -> &TaskPool {
+        self.generation.get()
+    }
+
+    pub fn meshing(&self) -> &TaskPool {
+        self.meshing.get()
+    }
+}
+
+/// Replaces the task pools when the threading config changes. The previous pools are kept until
+/// no task is in flight, since their tasks would be dropped with them.
+fn rebuild_task_pools(
+    config: Res<VoxelThreadingConfig>,
+    mut pools: ResMut<VoxelTaskPools>,
+    stats: Res<VoxelTaskStats>,
+) {
+    if !pools.retired.is_empty() && stats.generation.in_flight == 0 && stats.meshing.in_flight == 0
+    {
+        pools.retired.clear();
+    }
+
+    if !config.is_changed() || !pools.needs_rebuild(&config) {
+        return;
+    }
+
+    let previous = std::mem::replace(&mut *pools, VoxelTaskPools::new(*config));
+    pools.retired = previous.retired;
+    pools
+        .retired
+        .extend([previous.generation, previous.meshing]);
+}
+
+/// Queue length, tasks in flight and budget of a stage
+#[derive(Debug, Default)]
+pub struct StageStats {
+    /// Updated by the stage when it spawns its tasks
+    pub queued: usize,
+    pub in_flight: usize,
+    pub budget: usize,
+    latencies: Arc<Latencies>,
+}
+
+impl StageStats {
+    /// Tasks the stage can spawn without exceeding its budget
+    pub fn available(&self) -> usize {
+        self.budget.saturating_sub(self.in_flight)
+    }
+
+    /// Call when spawning a task, the returned handle is used to report its latency
+    pub fn start_task(&mut self) -> Arc<Latencies> {
+        self.in_flight += 1;
+        Arc::clone(&self.latencies)
+    }
+
+    /// Call when the result of a task is handled, even if it's dropped
+    pub fn finish_task(&mut self) {
+        self.in_flight = self.in_flight.saturating_sub(1);
+    }
+}
+
+/// Latencies reported by the tasks of a stage, averaged every frame
+#[derive(Debug, Default)]
+pub struct Latencies {
+    total_nanos: AtomicU64,
+    count: AtomicU64,
+}
+
+impl Latencies {
+    pub fn record(&self, latency: Duration) {
+        self.total_nanos
+            .fetch_add(latency.as_nanos() as u64, Ordering::Relaxed);
+        self.count.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Average of the latencies recorded since the last call
+    fn take_average(&self) -> Option<Duration> {
+        let count = self.count.swap(0, Ordering::Relaxed);
+        let total = self.total_nanos.swap(0, Ordering::Relaxed);
+        (count > 0).then(|| Duration::from_nanos(total / count))
+    }
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct VoxelTaskStats {
+    pub generation: StageStats,
+    pub meshing: StageStats,
+}
+
+fn update_task_budgets(config: Res<VoxelThreadingConfig>, mut stats: ResMut<VoxelTaskStats>) {
+    let (generation, meshing) = config.task_budgets(stats.generation.queued, stats.meshing.queued);
+    stats.generation.budget = generation;
+    stats.meshing.budget = meshing;
+}
+
+fn measure_task_stats(mut diagnostics: Diagnostics, stats: Res<VoxelTaskStats>) {
+    diagnostics.add_measurement(GENERATION_QUEUE, || stats.generation.queued as f64);
+    diagnostics.add_measurement(MESHING_QUEUE, || stats.meshing.queued as f64);
+    diagnostics.add_measurement(GENERATION_TASKS, || stats.generation.in_flight as f64);
+    diagnostics.add_measurement(MESHING_TASKS, || stats.meshing.in_flight as f64);
+
+    if let Some(latency) = stats.generation.latencies.take_average() {
+        diagnostics.add_measurement(GENERATION_LATENCY, || latency.as_secs_f64() * 1000.0);
+    }
+    if let Some(latency) = stats.meshing.latencies.take_average() {
+        diagnostics.add_measurement(MESHING_LATENCY, || latency.as_secs_f64() * 1000.0);
+    }
+}
@@ -0,0 +1,148 @@
+use bevy::{input::mouse::MouseMotion, prelude::*};
+
+use crate::collision::{move_character_controllers, CharacterController};
+
+/// Walking on the planet: radial gravity, "up" following the planet and mouse/keyboard controls
+pub struct WalkerPlugin;
+
+impl Plugin for WalkerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PlanetGravity>()
+            .register_type::<PlanetWalker>()
+            .add_systems(
+                Update,
+                (apply_planet_gravity, align_walkers_to_planet, walk)
+                    .chain()
+                    .before(move_character_controllers),
+            );
+    }
+}
+
+/// Gravity pulling towards the center of the planet
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct PlanetGravity {
+    pub center: Vec3,
+    pub acceleration: f32,
+}
+
+impl Default for PlanetGravity {
+    fn default() -> Self {
+        Self {
+            center: Vec3::ZERO,
+            acceleration: 9.81,
+        }
+    }
+}
+
+/// Character walking on the planet, its camera must be a child entity so it can look up and down
+/// without tilting the capsule
+#[derive(Component, Debug, Clone, Reflect)]
+pub struct PlanetWalker {
+    pub enabled: bool,
+    pub speed: f32,
+    pub run_speed: f32,
+    pub jump_speed: f32,
+    pub mouse_sensitivity: f32,
+    /// How fast the body turns to match the local up, in 1/s
+    pub alignment_rate: f32,
+    pub pitch: f32,
+}
+
+impl Default for PlanetWalker {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            speed: 6.0,
+            run_speed: 20.0,
+            jump_speed: 6.0,
+            mouse_sensitivity: 0.002,
+            alignment_rate: 8.0,
+            pitch: 0.0,
+        }
+    }
+}
+
+fn apply_planet_gravity(
+    gravity: Res<PlanetGravity>,
+    mut walkers: Query<(&Transform, &mut CharacterController), With<PlanetWalker>>,
+) {
+    for (transform, mut controller) in walkers.iter_mut() {
+        let down = (gravity.center - transform.translation).normalize_or_zero();
+        controller.gravity = down * gravity.acceleration;
+    }
+}
+
+/// Rotates the walkers so that their up axis points away from the planet center
+fn align_walkers_to_planet(
+    time: Res<Time>,
+    mut walkers: Query<(&mut Transform, &PlanetWalker, &CharacterController)>,
+) {
+    for (mut transform, walker, controller) in walkers.iter_mut() {
+        let target_up = -controller.gravity.normalize_or_zero();
+        if target_up == Vec3::ZERO {
+            continue;
+        }
+
+        let alignment = Quat::from_rotation_arc(transform.up(), target_up);
+        let t = (walker.alignment_rate * time.delta_seconds()).min(1.0);
+        transform.rotation = Quat::IDENTITY.slerp(alignment, t) * transform.rotation;
+    }
+}
+
+fn walk(
+    keys: Res<Input<KeyCode>>,
+    mut mouse_motion: EventReader<MouseMotion>,
+    mut walkers: Query<(
+        &mut Transform,
+        &mut PlanetWalker,
+        &mut CharacterController,
+        &Children,
+    )>,
+    mut cameras: Query<&mut Transform, (With<Camera>, Without<PlanetWalker>)>,
+) {
+    let mouse_delta: Vec2 = mouse_motion.iter().map(|m| m.delta).sum();
+
+    for (mut transform, mut walker, mut controller, children) in walkers.iter_mut() {
+        if !walker.enabled {
+            continue;
+        }
+
+        // Yaw turns the body around its up axis, pitch only tilts the camera
+        let up = transform.up();
+        transform.rotate_axis(up, -mouse_delta.x * walker.mouse_sensitivity);
+        walker.pitch = (walker.pitch - mouse_delta.y * walker.mouse_sensitivity)
+            .clamp(-std::f32::consts::FRAC_PI_2, std::f32::consts::FRAC_PI_2);
+
+        for &child in children.iter() {
+            if let Ok(mut camera_transform) = cameras.get_mut(child) {
+                camera_transform.rotation = Quat::from_rotation_x(walker.pitch);
+            }
+        }
+
+        let mut direction = Vec3::ZERO;
+        for (key, axis) in [
+            (KeyCode::W, transform.forward()),
+            (KeyCode::S, transform.back()),
+            (KeyCode::A, transform.left()),
+            (KeyCode::D, transform.right()),
+        ] {
+            if keys.pressed(key) {
+                direction += axis;
+            }
+        }
+
+        let speed = if keys.pressed(KeyCode::ShiftLeft) {
+            walker.run_speed
+        } else {
+            walker.speed
+        };
+
+        // Only the tangential velocity is driven by the keys, the radial one belongs to gravity
+        let radial_velocity = up * controller.velocity.dot(up);
+        controller.velocity = radial_velocity + direction.normalize_or_zero() * speed;
+
+        if controller.grounded && keys.just_pressed(KeyCode::Space) {
+            controller.velocity += up * walker.jump_speed;
+        }
+    }
+}
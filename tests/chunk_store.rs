@@ -0,0 +1,60 @@
+//! The chunk store is shared between the main thread and the tasks, the chunks they hold must not
+//! change under them.
+
+use std::sync::Arc;
+
+use bevy::{prelude::*, tasks::TaskPoolBuilder};
+use surface_nets_experiment::{
+    chunk::{Chunk, ChunkKey, Sd8},
+    chunk_map::ChunkMap,
+};
+
+#[test]
+fn edits_copy_the_chunks_held_elsewhere() {
+    let chunk_map = ChunkMap::default();
+    let key = ChunkKey(IVec3::ZERO);
+    chunk_map.storage.insert(key, Arc::new(Chunk::new_empty()));
+
+    let held = chunk_map.storage.get(&key).unwrap();
+    chunk_map
+        .storage
+        .edit(&key, |chunk| chunk.set_voxel(IVec3::ZERO, Sd8(-1)));
+
+    assert_eq!(held.get_voxel(IVec3::ZERO).0, Sd8::MAX.0);
+    assert_eq!(chunk_map.get_voxel(IVec3::ZERO).map(|sd| sd.0), Some(-1));
+}
+
+#[test]
+fn edits_of_missing_chunks_do_nothing() {
+    let chunk_map = ChunkMap::default();
+
+    assert_eq!(chunk_map.storage.edit(&ChunkKey(IVec3::ZERO), |_| ()), None);
+    assert!(chunk_map.storage.is_empty());
+}
+
+#[test]
+fn tasks_insert_and_read_in_parallel() {
+    let chunk_map = ChunkMap::default();
+    let keys: Vec<_> = (-4..4)
+        .flat_map(|x| (-4..4).map(move |z| ChunkKey(IVec3::new(x, 0, z))))
+        .collect();
+
+    let pool = TaskPoolBuilder::default().num_threads(4).build();
+    pool.scope(|s| {
+        for &key in &keys {
+            let chunk_map = &chunk_map;
+            s.spawn(async move {
+                chunk_map.storage.insert(key, Arc::new(Chunk::new_empty()));
+                // Reading the neighbors while the other tasks insert theirs
+                chunk_map.copy_chunk_neighborhood(key);
+            });
+        }
+    });
+
+    let mut stored = chunk_map.storage.keys();
+    stored.sort_unstable_by_key(|k| k.to_array());
+    let mut expected = keys;
+    expected.sort_unstable_by_key(|k| k.to_array());
+    assert_eq!(stored, expected);
+    assert_eq!(chunk_map.storage.len(), expected.len());
+}
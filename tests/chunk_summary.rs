@@ -0,0 +1,77 @@
+//! The summaries stored with the chunks let the meshing and the culling skip the chunks that are
+//! only air or only matter, they must follow the edits.
+
+mod common;
+
+use std::sync::Arc;
+
+use bevy::prelude::*;
+use common::chunk;
+use surface_nets_experiment::{
+    chunk::{Chunk, ChunkKey, ChunkSummary, Fill, Sd8},
+    chunk_map::{padding_neighbors, ChunkMap},
+    culling::{Face, FaceConnectivity},
+};
+
+#[test]
+fn uniform_chunks() {
+    let empty = ChunkSummary::from_chunk(&Chunk::new_empty());
+    assert_eq!(empty.fill(), Fill::Empty);
+    assert_eq!(empty.faces, [Fill::Empty; 6]);
+    assert!(!empty.crosses_surface());
+
+    let solid = ChunkSummary::from_chunk(&chunk(|_| true));
+    assert_eq!(solid.fill(), Fill::Solid);
+    assert_eq!(solid.faces, [Fill::Solid; 6]);
+    assert_eq!((solid.min.0, solid.max.0), (-1, -1));
+}
+
+#[test]
+fn faces_of_a_half_solid_chunk() {
+    let summary = ChunkSummary::from_chunk(&chunk(|p| p.x < 16));
+
+    assert!(summary.crosses_surface());
+    assert_eq!(summary.face(Face::NegX), Fill::Solid);
+    assert_eq!(summary.face(Face::PosX), Fill::Empty);
+    for face in [Face::NegY, Face::PosY, Face::NegZ, Face::PosZ] {
+        assert_eq!(summary.face(face), Fill::Mixed, "{face:?}");
+    }
+}
+
+#[test]
+fn edits_update_the_summary() {
+    let chunk_map = ChunkMap::default();
+    let key = ChunkKey(IVec3::ZERO);
+    chunk_map.storage.insert(key, Arc::new(Chunk::new_empty()));
+    assert_eq!(chunk_map.storage.summary(&key).unwrap().fill(), Fill::Empty);
+
+    chunk_map
+        .storage
+        .edit(&key, |chunk| chunk.set_voxel(IVec3::new(0, 5, 5), Sd8(-1)));
+
+    let summary = chunk_map.storage.summary(&key).unwrap();
+    assert_eq!(summary.fill(), Fill::Mixed);
+    assert_eq!(summary.face(Face::NegX), Fill::Mixed);
+    assert_eq!(summary.face(Face::PosX), Fill::Empty);
+}
+
+#[test]
+fn uniform_neighborhoods() {
+    let chunk_map = ChunkMap::default();
+    let key = ChunkKey(IVec3::ZERO);
+    let solid = Arc::new(chunk(|_| true));
+
+    // Surrounded by chunks that aren't loaded, which are air
+    chunk_map.storage.insert(key, Arc::new(Chunk::new_empty()));
+    assert!(chunk_map.is_neighborhood_uniform(key));
+    assert_eq!(chunk_map.face_connectivity(key), FaceConnectivity::ALL);
+
+    chunk_map.storage.insert(key, Arc::clone(&solid));
+    assert!(!chunk_map.is_neighborhood_uniform(key));
+    assert_eq!(chunk_map.face_connectivity(key), FaceConnectivity::NONE);
+
+    for k in padding_neighbors(key) {
+        chunk_map.storage.insert(k, Arc::clone(&solid));
+    }
+    assert!(chunk_map.is_neighborhood_uniform(key));
+}
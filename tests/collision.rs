@@ -0,0 +1,103 @@
+//! Capsules overlapping the terrain are pushed out along its normal, the ones in the air are left
+//! where they are.
+
+mod common;
+
+use std::sync::Arc;
+
+use bevy::prelude::*;
+use common::key;
+use surface_nets_experiment::{
+    chunk::{Chunk, Sd8, CHUNK_SIDE},
+    chunk_map::ChunkMap,
+    collision::{resolve_capsule, CharacterController},
+};
+
+/// Height of the ground in the planar chunk, in voxels
+const GROUND: f32 = 15.5;
+
+/// Chunk filled below `GROUND`, with the signed distances to its surface
+fn planar_chunk() -> Chunk {
+    let mut chunk = Chunk::new_empty();
+    let side = CHUNK_SIDE as i32;
+    for z in 0..side {
+        for y in 0..side {
+            for x in 0..side {
+                chunk.set_voxel(IVec3::new(x, y, z), Sd8::from(y as f32 - GROUND));
+            }
+        }
+    }
+    chunk
+}
+
+fn planar_chunk_map() -> ChunkMap {
+    let chunk_map = ChunkMap::default();
+    chunk_map
+        .storage
+        .insert(key(0, 0, 0), Arc::new(planar_chunk()));
+    chunk_map
+}
+
+#[test]
+fn intersecting_capsules_are_pushed_up() {
+    let chunk_map = planar_chunk_map();
+    let controller = CharacterController::default();
+    // The bottom sphere is centered on the surface
+    let center = Vec3::new(16.0, GROUND + controller.half_height, 16.0);
+
+    let contact = resolve_capsule(
+        &chunk_map,
+        center,
+        Vec3::Y,
+        controller.radius,
+        controller.half_height,
+    )
+    .unwrap();
+
+    let normal = contact.normal.unwrap();
+    assert!(normal.abs_diff_eq(Vec3::Y, 1e-3), "{normal}");
+    assert!(contact.is_grounded(Vec3::NEG_Y));
+    assert_eq!(contact.position.xz(), center.xz());
+    let resting_height = GROUND + controller.half_height + controller.radius;
+    assert!(
+        (contact.position.y - resting_height).abs() < 0.02,
+        "{}",
+        contact.position
+    );
+}
+
+#[test]
+fn capsules_in_the_air_are_left_unchanged() {
+    let chunk_map = planar_chunk_map();
+    let controller = CharacterController::default();
+    let center = Vec3::new(16.0, GROUND + 8.0, 16.0);
+
+    let contact = resolve_capsule(
+        &chunk_map,
+        center,
+        Vec3::Y,
+        controller.radius,
+        controller.half_height,
+    )
+    .unwrap();
+
+    assert_eq!(contact.position, center);
+    assert_eq!(contact.normal, None);
+    assert!(!contact.is_grounded(Vec3::NEG_Y));
+}
+
+#[test]
+fn capsules_over_unloaded_chunks_are_not_resolved() {
+    let chunk_map = planar_chunk_map();
+    let controller = CharacterController::default();
+
+    let contact = resolve_capsule(
+        &chunk_map,
+        Vec3::new(-16.0, GROUND, 16.0),
+        Vec3::Y,
+        controller.radius,
+        controller.half_height,
+    );
+
+    assert!(contact.is_none());
+}
@@ -0,0 +1,27 @@
+//! Fixtures shared by the integration tests, each test crate only uses some of them.
+
+#![allow(dead_code)]
+
+use bevy::prelude::*;
+use surface_nets_experiment::chunk::{Chunk, ChunkKey, Sd8, CHUNK_SIDE};
+
+pub fn key(x: i32, y: i32, z: i32) -> ChunkKey {
+    ChunkKey(IVec3::new(x, y, z))
+}
+
+/// Chunk whose voxels are solid where `solid` is true
+pub fn chunk(solid: impl Fn(IVec3) -> bool) -> Chunk {
+    let mut chunk = Chunk::new_empty();
+    let side = CHUNK_SIDE as i32;
+    for z in 0..side {
+        for y in 0..side {
+            for x in 0..side {
+                let p = IVec3::new(x, y, z);
+                if solid(p) {
+                    chunk.set_voxel(p, Sd8(-1));
+                }
+            }
+        }
+    }
+    chunk
+}
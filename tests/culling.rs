@@ -0,0 +1,111 @@
+//! The chunks are culled by walking from the camera's chunk through the faces connected by open
+//! voxels, the chunks enclosed in solid ones are never reached.
+
+mod common;
+
+use bevy::{prelude::*, utils::HashMap};
+use common::{chunk, key};
+use surface_nets_experiment::{
+    chunk::Chunk,
+    chunk_map::CurrentChunks,
+    culling::{visible_chunks, Face, FaceConnectivity},
+};
+
+#[test]
+fn empty_and_solid_chunks() {
+    assert_eq!(
+        FaceConnectivity::from_chunk(&Chunk::new_empty()),
+        FaceConnectivity::ALL
+    );
+    assert_eq!(
+        FaceConnectivity::from_chunk(&chunk(|_| true)),
+        FaceConnectivity::NONE
+    );
+}
+
+#[test]
+fn walls_split_the_faces() {
+    let connectivity = FaceConnectivity::from_chunk(&chunk(|p| p.x == 16));
+
+    assert!(!connectivity.connects(Face::NegX, Face::PosX));
+    assert!(connectivity.connects(Face::NegX, Face::PosY));
+    assert!(connectivity.connects(Face::PosX, Face::NegZ));
+    assert!(connectivity.connects(Face::PosY, Face::NegY));
+}
+
+#[test]
+fn tunnels_connect_their_ends() {
+    let connectivity = FaceConnectivity::from_chunk(&chunk(|p| p.y != 16 || p.z != 16));
+
+    assert!(connectivity.connects(Face::NegX, Face::PosX));
+    assert!(connectivity.connects(Face::PosX, Face::NegX));
+    for face in [Face::NegY, Face::PosY, Face::NegZ, Face::PosZ] {
+        assert!(!connectivity.is_open(face));
+        assert!(!connectivity.connects(Face::NegX, face));
+    }
+}
+
+#[test]
+fn solid_chunks_block_the_view() {
+    let connectivity = HashMap::from([(key(1, 0, 0), FaceConnectivity::NONE)]);
+    let visible = visible_chunks(key(0, 0, 0), IVec3::ZERO, IVec3::new(2, 0, 0), |k| {
+        connectivity.get(&k).copied()
+    });
+
+    assert!(visible.contains(&key(1, 0, 0)));
+    assert!(!visible.contains(&key(2, 0, 0)));
+}
+
+#[test]
+fn enclosed_chunks_are_culled() {
+    // A shell of solid chunks around an empty one, seen from outside
+    let mut connectivity = HashMap::default();
+    for z in -1..=1 {
+        for y in -1..=1 {
+            for x in -1..=1 {
+                if (x, y, z) != (0, 0, 0) {
+                    connectivity.insert(key(x, y, z), FaceConnectivity::NONE);
+                }
+            }
+        }
+    }
+
+    let visible = visible_chunks(key(5, 5, 5), IVec3::splat(-2), IVec3::splat(2), |k| {
+        connectivity.get(&k).copied()
+    });
+
+    assert!(visible.contains(&key(1, 1, 1)));
+    assert!(visible.contains(&key(1, 0, 0)));
+    assert!(!visible.contains(&key(0, 0, 0)));
+    // Behind the shell
+    assert!(!visible.contains(&key(-1, -1, -1)));
+}
+
+#[test]
+fn walk_bounds_follow_the_loaded_chunks() {
+    let mut current_chunks = CurrentChunks::default();
+    assert_eq!(current_chunks.bounds(), None);
+
+    for (i, k) in [key(0, 0, 0), key(3, -2, 1), key(-1, 4, 1)]
+        .into_iter()
+        .enumerate()
+    {
+        current_chunks.add(k, Entity::from_raw(i as u32));
+    }
+    // Adding a chunk again doesn't count it twice
+    current_chunks.add(key(3, -2, 1), Entity::from_raw(3));
+    assert_eq!(
+        current_chunks.bounds(),
+        Some((IVec3::new(-1, -2, 0), IVec3::new(3, 4, 1)))
+    );
+
+    current_chunks.remove(key(3, -2, 1));
+    assert_eq!(
+        current_chunks.bounds(),
+        Some((IVec3::new(-1, 0, 0), IVec3::new(0, 4, 1)))
+    );
+
+    current_chunks.remove(key(0, 0, 0));
+    current_chunks.remove(key(-1, 4, 1));
+    assert_eq!(current_chunks.bounds(), None);
+}
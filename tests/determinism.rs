@@ -0,0 +1,71 @@
+//! The chunks must only depend on the `WorldSeed`, not on the run or the number of threads.
+
+use bevy::{prelude::*, tasks::TaskPoolBuilder};
+use surface_nets_experiment::{
+    chunk::{Chunk, ChunkKey},
+    generation::{ChunkGenerator, Generator, WorldSeed, GENERATOR},
+};
+
+/// Chunks on the surface, in a cave layer and in the core of the planet
+const KEYS: [ChunkKey; 5] = [
+    ChunkKey(IVec3::new(8, 0, 0)),
+    ChunkKey(IVec3::new(0, -9, 0)),
+    ChunkKey(IVec3::new(-6, 5, 4)),
+    ChunkKey(IVec3::new(3, 3, -6)),
+    ChunkKey(IVec3::ZERO),
+];
+
+/// FNV-1a, unlike the hashers of the standard library it's guaranteed to never change
+fn hash_chunk(chunk: &Chunk) -> u64 {
+    chunk.sdf.iter().fold(0xcbf2_9ce4_8422_2325, |hash, sd| {
+        (hash ^ sd.0 as u8 as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+fn hashes(generator: &dyn ChunkGenerator) -> Vec<u64> {
+    KEYS.iter()
+        .map(|&key| hash_chunk(&generator.generate_chunk(key)))
+        .collect()
+}
+
+#[test]
+fn same_chunks_for_every_thread_count() {
+    let expected = hashes(GENERATOR.as_ref());
+
+    for threads in [1, 2, 4] {
+        let pool = TaskPoolBuilder::default().num_threads(threads).build();
+        let generator: &dyn ChunkGenerator = GENERATOR.as_ref();
+        let mut chunks = pool.scope(|s| {
+            for (i, &key) in KEYS.iter().enumerate() {
+                s.spawn(async move { (i, hash_chunk(&generator.generate_chunk(key))) });
+            }
+        });
+        chunks.sort_unstable_by_key(|&(i, _)| i);
+
+        let actual: Vec<u64> = chunks.into_iter().map(|(_, hash)| hash).collect();
+        assert_eq!(actual, expected, "different chunks with {threads} threads");
+    }
+}
+
+#[test]
+fn same_chunks_for_the_same_seed() {
+    let seed = WorldSeed(1234);
+
+    assert_eq!(hashes(&Generator::new(seed)), hashes(&Generator::new(seed)));
+    assert_ne!(
+        hashes(&Generator::new(seed)),
+        hashes(&Generator::new(WorldSeed(seed.0 + 1)))
+    );
+}
+
+#[test]
+fn nearby_seeds_derive_distinct_sources() {
+    let mut derived: Vec<u64> = (0..4)
+        .flat_map(|s| (0..64).map(move |n| WorldSeed(s).derive(n)))
+        .collect();
+    let count = derived.len();
+    derived.sort_unstable();
+    derived.dedup();
+
+    assert_eq!(derived.len(), count);
+}
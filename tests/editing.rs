@@ -0,0 +1,80 @@
+//! An edited voxel is read by the chunk containing it and by the chunks holding it in their
+//! padding, only the sub-blocks of those chunks around the voxel are remeshed.
+
+use std::sync::Arc;
+
+use bevy::prelude::*;
+use surface_nets_experiment::{
+    chunk::{Chunk, ChunkKey, Extent3i, Sd8},
+    chunk_map::{ChunkMap, DirtyChunks, SubBlockMask, SUB_BLOCKS_PER_SIDE},
+    editing::{chunks_to_remesh, mark_edited},
+};
+
+fn loaded_chunks(min: i32, max: i32) -> ChunkMap {
+    let chunk_map = ChunkMap::default();
+    for p in Extent3i::from_min_and_max(IVec3::splat(min), IVec3::splat(max)).iter3() {
+        chunk_map
+            .storage
+            .insert(ChunkKey(p), Arc::new(Chunk::new_empty()));
+    }
+    chunk_map
+}
+
+fn sub_block(p: UVec3) -> SubBlockMask {
+    SubBlockMask(1 << (p.x + SUB_BLOCKS_PER_SIDE * (p.y + SUB_BLOCKS_PER_SIDE * p.z)))
+}
+
+#[test]
+fn corner_edits_remesh_the_chunks_sharing_the_corner() {
+    let chunk_map = loaded_chunks(-1, 2);
+    let mut dirty_chunks = DirtyChunks::default();
+
+    // The minimum corner of the chunk (1, 1, 1)
+    let corner = ChunkKey(IVec3::ONE);
+    let voxel = corner.min_point();
+    chunk_map.storage.edit(&corner, |chunk| {
+        chunk.set_voxel(IVec3::ZERO, Sd8::from(-1.0))
+    });
+    let extent = Extent3i::from_min_and_shape(voxel, IVec3::ONE);
+    mark_edited(&chunk_map, &extent, &mut dirty_chunks);
+
+    // The chunk and the ones on its negative side, which hold the voxel in their padding
+    let mut expected: Vec<_> = Extent3i::from_min_and_max(IVec3::ZERO, IVec3::ONE)
+        .iter3()
+        .map(ChunkKey)
+        .collect();
+    let mut remeshed: Vec<_> = chunks_to_remesh(&extent).collect();
+    remeshed.sort_unstable_by_key(|k| k.0.to_array());
+    expected.sort_unstable_by_key(|k| k.0.to_array());
+    assert_eq!(remeshed, expected);
+    assert_eq!(dirty_chunks.len(), expected.len());
+
+    // Only the sub-block touching the corner, the first one along the axes where the chunk
+    // contains the voxel and the last one along the others
+    for key in expected {
+        let block = key
+            .0
+            .to_array()
+            .map(|c| if c == 1 { 0 } else { SUB_BLOCKS_PER_SIDE - 1 });
+        assert_eq!(
+            dirty_chunks.get(&key),
+            Some(sub_block(UVec3::from(block))),
+            "{key:?}"
+        );
+    }
+}
+
+#[test]
+fn unloaded_neighbors_are_not_remeshed() {
+    let chunk_map = loaded_chunks(1, 2);
+    let mut dirty_chunks = DirtyChunks::default();
+
+    let extent = Extent3i::from_min_and_shape(ChunkKey(IVec3::ONE).min_point(), IVec3::ONE);
+    mark_edited(&chunk_map, &extent, &mut dirty_chunks);
+
+    assert_eq!(dirty_chunks.len(), 1);
+    assert_eq!(
+        dirty_chunks.get(&ChunkKey(IVec3::ONE)),
+        Some(sub_block(UVec3::ZERO))
+    );
+}
@@ -0,0 +1,61 @@
+//! The exported meshes merge the vertices duplicated along the chunk seams, and the formats that
+//! can't store an empty mesh refuse it.
+
+use surface_nets_experiment::export::{ExportFormat, MergedMesh, WELD_TOLERANCE};
+
+/// Two triangles sharing an edge at `x`, the vertices of the second one are `offset` apart
+fn seam(x: f32, offset: f32) -> MergedMesh {
+    MergedMesh {
+        positions: vec![
+            [x, 0.0, 0.0],
+            [x, 1.0, 0.0],
+            [x - 1.0, 0.0, 0.0],
+            [x + offset, 0.0, 0.0],
+            [x + offset, 1.0, 0.0],
+            [x + 1.0, 0.0, 0.0],
+        ],
+        normals: vec![[0.0, 0.0, 1.0]; 6],
+        indices: vec![0, 1, 2, 3, 5, 4],
+    }
+}
+
+#[test]
+fn seams_are_welded() {
+    let mut mesh = seam(0.0, WELD_TOLERANCE / 4.0);
+    mesh.weld(WELD_TOLERANCE);
+
+    assert_eq!(mesh.positions.len(), 4);
+    assert_eq!(mesh.triangle_count(), 2);
+}
+
+#[test]
+fn seams_across_cell_boundaries_are_welded() {
+    // Whatever the grid, some of these pairs are split by its cells
+    for x in [0.1, 0.4, 0.9, 2.45, -0.6] {
+        let mut mesh = seam(x * WELD_TOLERANCE, WELD_TOLERANCE / 5.0);
+        mesh.weld(WELD_TOLERANCE);
+
+        assert_eq!(mesh.positions.len(), 4, "{x}");
+    }
+}
+
+#[test]
+fn distant_vertices_are_kept() {
+    let mut mesh = seam(0.0, WELD_TOLERANCE * 2.0);
+    mesh.weld(WELD_TOLERANCE);
+
+    assert_eq!(mesh.positions.len(), 6);
+}
+
+#[test]
+fn empty_meshes_are_not_exported_to_gltf() {
+    let mut bytes = Vec::new();
+    assert!(ExportFormat::Gltf
+        .write(&MergedMesh::default(), &mut bytes)
+        .is_err());
+
+    assert!(ExportFormat::Gltf
+        .write(&seam(0.0, 0.0), &mut bytes)
+        .is_ok());
+    assert_eq!(&bytes[..4], b"glTF");
+}
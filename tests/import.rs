@@ -0,0 +1,130 @@
+//! The importers read the samples in the order of their formats, and refuse the files they can't
+//! sample.
+
+use std::io::Cursor;
+
+use bevy::prelude::*;
+use image::{ImageBuffer, ImageOutputFormat, Luma};
+use surface_nets_experiment::generation::{DensityVolume, Heightmap};
+
+const WIDTH: usize = 3;
+const HEIGHT: usize = 2;
+const SAMPLES: [u16; WIDTH * HEIGHT] = [0, 1000, 65535, 30000, 12345, 500];
+
+fn assert_samples(heightmap: &Heightmap) {
+    assert_eq!((heightmap.width(), heightmap.height()), (WIDTH, HEIGHT));
+
+    for (i, &sample) in SAMPLES.iter().enumerate() {
+        let pixel = Vec2::new((i % WIDTH) as f32, (i / WIDTH) as f32);
+        let expected = sample as f32 / u16::MAX as f32;
+        assert!(
+            (heightmap.sample(pixel) - expected).abs() < 1e-6,
+            "pixel {pixel}"
+        );
+    }
+}
+
+#[test]
+fn raw_heightmaps_round_trip() {
+    let bytes: Vec<u8> = SAMPLES.iter().flat_map(|s| s.to_le_bytes()).collect();
+
+    assert_samples(&Heightmap::from_raw(&bytes, WIDTH, HEIGHT).unwrap());
+    assert!(Heightmap::from_raw(&bytes, HEIGHT, HEIGHT).is_err());
+}
+
+#[test]
+fn png_heightmaps_round_trip() {
+    let image =
+        ImageBuffer::<Luma<u16>, _>::from_raw(WIDTH as u32, HEIGHT as u32, SAMPLES.to_vec())
+            .unwrap();
+    let mut bytes = Vec::new();
+    image
+        .write_to(&mut Cursor::new(&mut bytes), ImageOutputFormat::Png)
+        .unwrap();
+
+    assert_samples(&Heightmap::from_png(&bytes).unwrap());
+}
+
+#[test]
+fn empty_heightmaps_are_rejected() {
+    assert!(Heightmap::from_raw(&[], 0, 0).is_err());
+    assert!(Heightmap::from_raw(&[], 0, 4).is_err());
+}
+
+#[test]
+fn raw_volumes_round_trip() {
+    let size = UVec3::new(2, 3, 4);
+    let mut bytes = vec![0; 24];
+    // X varies the fastest, then Y, then Z
+    bytes[1 + 2 * (2 + 3 * 3)] = 255;
+    let volume = DensityVolume::from_raw(&bytes, size).unwrap();
+
+    assert_eq!(volume.size(), size);
+    assert_eq!(volume.sample(Vec3::new(1.0, 2.0, 3.0)), 1.0);
+    assert_eq!(volume.sample(Vec3::new(0.0, 2.0, 3.0)), 0.0);
+    assert!(DensityVolume::from_raw(&[], UVec3::ZERO).is_err());
+}
+
+fn chunk(id: &[u8; 4], content: &[u8]) -> Vec<u8> {
+    let mut bytes = id.to_vec();
+    bytes.extend((content.len() as u32).to_le_bytes());
+    bytes.extend(0u32.to_le_bytes());
+    bytes.extend(content);
+    bytes
+}
+
+/// MagicaVoxel file of a single model, with the voxels given in MagicaVoxel's Z up coordinates
+fn vox(size: [u32; 3], voxels: &[[u8; 3]]) -> Vec<u8> {
+    let size: Vec<u8> = size.iter().flat_map(|c| c.to_le_bytes()).collect();
+    let mut xyzi = (voxels.len() as u32).to_le_bytes().to_vec();
+    for &[x, y, z] in voxels {
+        xyzi.extend([x, y, z, 1]);
+    }
+    let children = [chunk(b"SIZE", &size), chunk(b"XYZI", &xyzi)].concat();
+
+    let mut bytes = b"VOX ".to_vec();
+    bytes.extend(150u32.to_le_bytes());
+    bytes.extend(b"MAIN");
+    bytes.extend(0u32.to_le_bytes());
+    bytes.extend((children.len() as u32).to_le_bytes());
+    bytes.extend(children);
+    bytes
+}
+
+#[test]
+fn vox_models_are_rotated_to_y_up() {
+    // Along +X, +Y and +Z (up) of MagicaVoxel from the corner at the origin
+    let bytes = vox([2, 3, 4], &[[0, 0, 0], [1, 0, 0], [0, 1, 0], [0, 0, 1]]);
+    let volume = DensityVolume::from_vox(&bytes).unwrap();
+
+    assert_eq!(volume.size(), UVec3::new(2, 4, 3));
+
+    // A rotation keeps the handedness, MagicaVoxel's +Y becomes -Z
+    let filled = [
+        Vec3::new(0.0, 0.0, 2.0),
+        Vec3::new(1.0, 0.0, 2.0),
+        Vec3::new(0.0, 0.0, 1.0),
+        Vec3::new(0.0, 1.0, 2.0),
+    ];
+    for p in filled {
+        assert_eq!(volume.sample(p), 1.0, "{p}");
+    }
+    let filled_count = (0..2)
+        .flat_map(|x| (0..4).flat_map(move |y| (0..3).map(move |z| UVec3::new(x, y, z))))
+        .filter(|p| volume.sample(p.as_vec3()) == 1.0)
+        .count();
+    assert_eq!(filled_count, filled.len());
+}
+
+#[test]
+fn empty_vox_models_are_rejected() {
+    assert!(DensityVolume::from_vox(&vox([0, 3, 4], &[])).is_err());
+    assert!(DensityVolume::from_vox(b"VOX ").is_err());
+}
+
+#[test]
+fn oversized_volumes_are_rejected() {
+    assert!(DensityVolume::from_raw(&[0; 8], UVec3::splat(u32::MAX)).is_err());
+    assert!(DensityVolume::from_vox(&vox([4096, 4096, 4096], &[])).is_err());
+    assert!(Heightmap::from_raw(&[0; 8], usize::MAX, 2).is_err());
+}
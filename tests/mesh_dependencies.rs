@@ -0,0 +1,165 @@
+//! A chunk is meshed with the voxels of its positive-side neighbors in its padding, it must wait
+//! for the ones that are requested and be remeshed when the other ones arrive.
+
+mod common;
+
+use std::sync::Arc;
+
+use bevy::prelude::*;
+use common::key;
+use surface_nets_experiment::{
+    chunk::{Chunk, ChunkKey, ChunkSummary},
+    chunk_map::{padding_dependents, padding_neighbors, ChunkMap, CurrentChunks, DirtyChunks},
+};
+
+/// Requests the chunks in `requested`, of which `generated` are in the chunk map
+fn world(requested: &[ChunkKey], generated: &[ChunkKey]) -> (ChunkMap, CurrentChunks) {
+    let chunk_map = ChunkMap::default();
+    let mut current_chunks = CurrentChunks::default();
+
+    for (i, &k) in requested.iter().enumerate() {
+        current_chunks.add(k, Entity::from_raw(i as u32));
+    }
+    for &k in generated {
+        chunk_map.storage.insert(k, Arc::new(Chunk::new_empty()));
+    }
+
+    (chunk_map, current_chunks)
+}
+
+fn cube(min: i32, max: i32) -> Vec<ChunkKey> {
+    let mut keys = Vec::new();
+    for z in min..=max {
+        for y in min..=max {
+            for x in min..=max {
+                keys.push(key(x, y, z));
+            }
+        }
+    }
+    keys
+}
+
+#[test]
+fn padding_neighbors_are_the_positive_side() {
+    for k in [key(0, 0, 0), key(-1, -1, -1), key(3, -7, 12)] {
+        let mut neighbors: Vec<_> = padding_neighbors(k).map(|n| n.0 - k.0).collect();
+        neighbors.sort_unstable_by_key(|v| v.to_array());
+
+        let mut expected: Vec<_> = cube(0, 1).into_iter().map(|n| n.0).collect();
+        expected.retain(|&v| v != IVec3::ZERO);
+        expected.sort_unstable_by_key(|v| v.to_array());
+
+        assert_eq!(neighbors, expected, "neighbors of {k:?}");
+    }
+}
+
+#[test]
+fn padding_dependents_are_the_inverse_of_neighbors() {
+    for k in [key(0, 0, 0), key(-1, -1, -1), key(3, -7, 12)] {
+        let dependents: Vec<_> = padding_dependents(k).collect();
+        assert_eq!(dependents.len(), 7);
+
+        for d in dependents {
+            assert!(
+                padding_neighbors(d).any(|n| n == k),
+                "{d:?} doesn't read {k:?}"
+            );
+        }
+        for n in padding_neighbors(k) {
+            assert!(
+                padding_dependents(n).any(|d| d == k),
+                "{n:?} isn't read by {k:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn ready_when_every_neighbor_is_generated() {
+    let keys = cube(0, 1);
+    let (chunk_map, current_chunks) = world(&keys, &keys);
+
+    assert!(chunk_map.is_ready_to_mesh(key(0, 0, 0), &current_chunks));
+}
+
+#[test]
+fn not_ready_before_being_generated() {
+    let keys = cube(0, 1);
+    let generated: Vec<_> = keys
+        .iter()
+        .copied()
+        .filter(|&k| k != key(0, 0, 0))
+        .collect();
+    let (chunk_map, current_chunks) = world(&keys, &generated);
+
+    assert!(!chunk_map.is_ready_to_mesh(key(0, 0, 0), &current_chunks));
+}
+
+#[test]
+fn waits_for_requested_neighbors() {
+    // Only the corner neighbor is still generating
+    let keys = cube(0, 1);
+    let generated: Vec<_> = keys
+        .iter()
+        .copied()
+        .filter(|&k| k != key(1, 1, 1))
+        .collect();
+    let (chunk_map, current_chunks) = world(&keys, &generated);
+
+    assert!(!chunk_map.is_ready_to_mesh(key(0, 0, 0), &current_chunks));
+}
+
+#[test]
+fn absent_neighbors_dont_block() {
+    // A lone chunk, at the edge of the loaded region
+    let (chunk_map, current_chunks) = world(&[key(0, 0, 0)], &[key(0, 0, 0)]);
+
+    assert!(chunk_map.is_ready_to_mesh(key(0, 0, 0), &current_chunks));
+}
+
+#[test]
+fn negative_side_neighbors_dont_block() {
+    // Every neighbor on the negative side is still generating
+    let keys = cube(-1, 1);
+    let generated = cube(0, 1);
+    let (chunk_map, current_chunks) = world(&keys, &generated);
+
+    assert!(chunk_map.is_ready_to_mesh(key(0, 0, 0), &current_chunks));
+}
+
+#[test]
+fn late_neighbors_redirty_the_chunks_reading_them() {
+    let (chunk_map, _) = world(&[], &cube(-1, 0));
+    let mut dirty_chunks = DirtyChunks::default();
+
+    // The chunks at x = -1 are too far to read (1, 0, 0) in their padding
+    chunk_map.insert_generated(
+        key(1, 0, 0),
+        Chunk::new_empty(),
+        ChunkSummary::EMPTY,
+        &mut dirty_chunks,
+    );
+
+    let mut dirty: Vec<_> = dirty_chunks.keys().map(|k| k.to_array()).collect();
+    dirty.sort_unstable();
+    assert_eq!(
+        dirty,
+        [[0, -1, -1], [0, -1, 0], [0, 0, -1], [0, 0, 0], [1, 0, 0]]
+    );
+}
+
+#[test]
+fn late_neighbors_dont_redirty_missing_chunks() {
+    let (chunk_map, _) = world(&[], &[]);
+    let mut dirty_chunks = DirtyChunks::default();
+
+    chunk_map.insert_generated(
+        key(0, 0, 0),
+        Chunk::new_empty(),
+        ChunkSummary::EMPTY,
+        &mut dirty_chunks,
+    );
+
+    assert_eq!(dirty_chunks.len(), 1);
+    assert!(dirty_chunks.contains(&key(0, 0, 0)));
+}
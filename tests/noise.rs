@@ -0,0 +1,21 @@
+//! The curves of the noise stack remap the noises piecewise linearly, their points must have
+//! distinct x coordinates.
+
+use surface_nets_experiment::generation::Curve;
+
+#[test]
+fn curves_interpolate_between_their_points() {
+    let curve = Curve::new([(1.0, 10.0), (-1.0, 0.0), (0.0, 2.0)]);
+
+    assert_eq!(curve.evaluate(-2.0), 0.0);
+    assert_eq!(curve.evaluate(-0.5), 1.0);
+    assert_eq!(curve.evaluate(0.0), 2.0);
+    assert_eq!(curve.evaluate(0.5), 6.0);
+    assert_eq!(curve.evaluate(2.0), 10.0);
+}
+
+#[test]
+#[should_panic(expected = "distinct x")]
+fn curves_reject_points_with_the_same_x() {
+    Curve::new([(0.0, 0.0), (0.5, 1.0), (0.5, 2.0)]);
+}
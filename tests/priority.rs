@@ -0,0 +1,76 @@
+//! The chunks the player looks at are generated and meshed first, then the ones around the
+//! viewer, and the ones the generator expects to be empty or solid last.
+
+use bevy::{
+    prelude::*,
+    render::{camera::CameraProjection, primitives::Frustum},
+};
+use surface_nets_experiment::{
+    chunk::ChunkKey,
+    generation::GENERATOR,
+    priority::{chunk_priority, ChunkViewer, PrioritySettings, SurfaceHint},
+};
+
+const SURFACE: SurfaceHint = SurfaceHint(true);
+
+/// Viewer at the origin looking down -Z, with the frustum of the default perspective camera
+fn viewer() -> ChunkViewer {
+    let view = Transform::from_translation(Vec3::ZERO).looking_to(Vec3::NEG_Z, Vec3::Y);
+    let projection = PerspectiveProjection::default().get_projection_matrix();
+
+    ChunkViewer {
+        position: view.translation,
+        forward: view.forward(),
+        frustum: Some(Frustum::from_view_projection(
+            &(projection * view.compute_matrix().inverse()),
+        )),
+    }
+}
+
+fn priority(key: IVec3, surface: SurfaceHint) -> f32 {
+    chunk_priority(
+        ChunkKey(key),
+        surface,
+        &viewer(),
+        &PrioritySettings::default(),
+    )
+}
+
+#[test]
+fn closer_chunks_come_first() {
+    assert!(priority(IVec3::new(0, 0, -1), SURFACE) < priority(IVec3::new(0, 0, -4), SURFACE));
+}
+
+#[test]
+fn chunks_in_view_come_before_the_ones_behind() {
+    assert!(priority(IVec3::new(0, 0, -3), SURFACE) < priority(IVec3::new(0, 0, 2), SURFACE));
+}
+
+#[test]
+fn chunks_in_the_frustum_come_before_the_ones_beside() {
+    // At the same distance, one in the frustum and one to the side of it
+    assert!(priority(IVec3::new(0, 0, -4), SURFACE) < priority(IVec3::new(3, 0, -3), SURFACE));
+}
+
+#[test]
+fn chunks_without_surface_come_last() {
+    let key = IVec3::new(0, 0, -2);
+    assert!(priority(key, SURFACE) < priority(key, SurfaceHint(false)));
+}
+
+#[test]
+fn surface_hints_of_the_planet() {
+    // The core of the planet, the sky and a chunk on the surface
+    assert!(!*SurfaceHint::new(
+        ChunkKey(IVec3::ZERO),
+        GENERATOR.as_ref()
+    ));
+    assert!(!*SurfaceHint::new(
+        ChunkKey(IVec3::new(0, 20, 0)),
+        GENERATOR.as_ref()
+    ));
+    assert!(*SurfaceHint::new(
+        ChunkKey(IVec3::new(8, 0, 0)),
+        GENERATOR.as_ref()
+    ));
+}
@@ -0,0 +1,49 @@
+//! The adaptive scheduling moves the task budget to the stage with the longest queue, without
+//! starving the other one.
+
+use surface_nets_experiment::scheduling::{Scheduling, VoxelThreadingConfig};
+
+fn config(scheduling: Scheduling) -> VoxelThreadingConfig {
+    VoxelThreadingConfig {
+        generation_threads: 2,
+        meshing_threads: 2,
+        use_async_compute_pool: false,
+        scheduling,
+        tasks_per_thread: 2,
+    }
+}
+
+#[test]
+fn fixed_budgets_ignore_the_queues() {
+    let config = config(Scheduling::Fixed);
+
+    for queues in [(0, 0), (100, 0), (0, 100), (3, 97)] {
+        assert_eq!(config.task_budgets(queues.0, queues.1), (4, 4));
+    }
+}
+
+#[test]
+fn adaptive_budgets_follow_the_queues() {
+    let config = config(Scheduling::Adaptive);
+
+    assert_eq!(config.task_budgets(0, 0), (4, 4));
+    assert_eq!(config.task_budgets(10, 0), (8, 0));
+    assert_eq!(config.task_budgets(0, 10), (0, 8));
+    assert_eq!(config.task_budgets(30, 10), (6, 2));
+}
+
+#[test]
+fn adaptive_budgets_keep_a_task_for_each_queue() {
+    let config = config(Scheduling::Adaptive);
+
+    assert_eq!(config.task_budgets(1, 1000), (1, 7));
+    assert_eq!(config.task_budgets(1000, 1), (7, 1));
+}
+
+#[test]
+fn default_stages_have_at_least_two_threads() {
+    let config = VoxelThreadingConfig::default();
+
+    assert!(config.generation_threads >= 2);
+    assert!(config.meshing_threads >= 2);
+}
@@ -0,0 +1,127 @@
+//! Surface nets with sharp features places the vertices of the cells crossing the creases and
+//! corners of the field on them, instead of rounding them off.
+
+use bevy::prelude::*;
+use fast_surface_nets::ndshape::ConstShape;
+use surface_nets_experiment::{
+    chunk::{PaddedChunkShape, Sd8, PADDED_CHUNK_SIZE},
+    meshing::{feature_vertex, MeshBuffer, Mesher, MeshingSettings, PaddedSdf, Qef},
+};
+
+/// Corners of the cube, the same fraction of a voxel on every axis
+const CUBE_MIN: Vec3 = Vec3::splat(8.3);
+const CUBE_MAX: Vec3 = Vec3::splat(23.7);
+
+fn padded_sdf(sdf: impl Fn(Vec3) -> f32) -> PaddedSdf {
+    let mut padded_sdf = [Sd8::MAX; PADDED_CHUNK_SIZE];
+    for (i, sd) in padded_sdf.iter_mut().enumerate() {
+        let p = PaddedChunkShape::delinearize(i as u32);
+        *sd = sdf(UVec3::from(p).as_vec3()).into();
+    }
+    padded_sdf
+}
+
+fn cube_sdf(p: Vec3) -> f32 {
+    let center = (CUBE_MIN + CUBE_MAX) / 2.0;
+    let q = (p - center).abs() - (CUBE_MAX - CUBE_MIN) / 2.0;
+    q.max(Vec3::ZERO).length() + q.max_element().min(0.0)
+}
+
+fn mesh_cube(sharp_features: bool) -> Vec<Vec3> {
+    let settings = MeshingSettings {
+        sharp_features,
+        ..default()
+    };
+    let mut buffer = MeshBuffer::default();
+    settings.mesher().mesh(&padded_sdf(cube_sdf), &mut buffer);
+    buffer.positions.into_iter().map(Vec3::from).collect()
+}
+
+fn corners() -> impl Iterator<Item = Vec3> {
+    (0..8).map(|i| {
+        Vec3::select(
+            BVec3::new(i & 1 != 0, i & 2 != 0, i & 4 != 0),
+            CUBE_MAX,
+            CUBE_MIN,
+        )
+    })
+}
+
+fn nearest(vertices: &[Vec3], p: Vec3) -> f32 {
+    vertices
+        .iter()
+        .map(|v| v.distance(p))
+        .fold(f32::INFINITY, f32::min)
+}
+
+#[test]
+fn vertices_land_on_the_corners() {
+    let sharp = mesh_cube(true);
+    let smooth = mesh_cube(false);
+
+    for corner in corners() {
+        assert!(nearest(&sharp, corner) < 0.1, "{corner}");
+        assert!(nearest(&smooth, corner) > 0.3, "{corner}");
+    }
+}
+
+#[test]
+fn vertices_land_on_the_edges() {
+    let vertices = mesh_cube(true);
+
+    // Each cell along an edge, away from the corners, has a vertex on the edge
+    for axis in 0..3 {
+        let (u, v) = ((axis + 1) % 3, (axis + 2) % 3);
+        for corner in corners().filter(|c| c[axis] == CUBE_MIN[axis]) {
+            let start = CUBE_MIN[axis].ceil() as u32 + 1;
+            let end = CUBE_MAX[axis].floor() as u32 - 1;
+
+            for slab in start..end {
+                let distance = vertices
+                    .iter()
+                    .filter(|p| (slab as f32..slab as f32 + 1.0).contains(&p[axis]))
+                    .map(|p| Vec2::new(p[u] - corner[u], p[v] - corner[v]).length())
+                    .fold(f32::INFINITY, f32::min);
+                assert!(distance < 0.05, "edge through {corner} along {axis}");
+            }
+        }
+    }
+}
+
+#[test]
+fn vertices_of_a_plane_stay_in_their_cells() {
+    let plane = |p: Vec3| (p.z - 10.3 + 0.2 * p.x) / 1.04f32.sqrt();
+    let padded_sdf = padded_sdf(plane);
+
+    let mut count = 0;
+    for z in 0..33 {
+        for y in 0..33 {
+            for x in 0..33 {
+                let cell = UVec3::new(x, y, z);
+                let Some((position, _)) = feature_vertex(&padded_sdf, cell) else {
+                    continue;
+                };
+                count += 1;
+
+                let min = cell.as_vec3();
+                assert!(position.cmpge(min).all() && position.cmple(min + 1.0).all());
+                assert!(plane(position).abs() < 0.01, "{position}");
+            }
+        }
+    }
+    assert!(count > 0);
+}
+
+#[test]
+fn singular_qefs_solve_to_the_mass_point() {
+    // Parallel planes leave the system singular along them
+    let mut qef = Qef::default();
+    qef.add(Vec3::new(0.2, 0.5, 0.5), Vec3::X);
+    qef.add(Vec3::new(0.3, 0.1, 0.9), Vec3::X);
+    assert!(qef.solve(0.05).distance(qef.mass_point()) < 1e-5);
+
+    // Crossings without a normal only have the mass point
+    let mut qef = Qef::default();
+    qef.add(Vec3::new(0.25, 0.5, 0.5), Vec3::ZERO);
+    assert!(qef.solve(0.05).distance(qef.mass_point()) < 1e-5);
+}
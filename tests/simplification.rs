@@ -0,0 +1,102 @@
+//! The simplification merges the coplanar triangles of the meshes, but the vertices shared with the
+//! neighboring chunks must stay where they are for the seams to line up.
+
+use bevy::prelude::*;
+use surface_nets_experiment::meshing::{
+    is_near_boundary, simplify, MeshBuffer, SimplificationSettings, DETAIL_LEVELS,
+};
+
+/// Flat grid of quads crossing the whole chunk, one per cell
+fn flat_patch() -> MeshBuffer {
+    const SIDE: u32 = 33;
+    let mut buffer = MeshBuffer::default();
+
+    for y in 0..SIDE {
+        for x in 0..SIDE {
+            buffer.positions.push([x as f32, y as f32, 16.5]);
+            buffer.normals.push([0.0, 0.0, 1.0]);
+        }
+    }
+    for y in 0..SIDE - 1 {
+        for x in 0..SIDE - 1 {
+            let i = y * SIDE + x;
+            buffer
+                .indices
+                .extend([i, i + 1, i + SIDE, i + 1, i + SIDE + 1, i + SIDE]);
+        }
+    }
+
+    buffer
+}
+
+fn triangle_count(buffer: &MeshBuffer) -> usize {
+    buffer.indices.len() / 3
+}
+
+#[test]
+fn flat_patches_collapse() {
+    let mut buffer = flat_patch();
+    let original = triangle_count(&buffer);
+
+    simplify(&mut buffer, 0.0, 0.01);
+
+    assert!(triangle_count(&buffer) < original / 2);
+    assert!(buffer.positions.iter().all(|p| p[2] == 16.5));
+    assert_eq!(buffer.positions.len(), buffer.normals.len());
+}
+
+#[test]
+fn boundary_vertices_stay_in_place() {
+    let original = flat_patch();
+    let mut buffer = flat_patch();
+
+    simplify(&mut buffer, 0.0, 0.01);
+
+    let boundary = original
+        .positions
+        .iter()
+        .filter(|&&p| is_near_boundary(Vec3::from(p).as_dvec3()));
+    for p in boundary {
+        assert!(buffer.positions.contains(p), "{p:?}");
+    }
+
+    // Only the boundary is left since the inside is flat
+    assert!(buffer
+        .positions
+        .iter()
+        .all(|&p| is_near_boundary(Vec3::from(p).as_dvec3())));
+}
+
+#[test]
+fn full_detail_keeps_the_mesh() {
+    let mut buffer = flat_patch();
+    let original = triangle_count(&buffer);
+
+    simplify(&mut buffer, 1.0, 0.01);
+
+    assert_eq!(triangle_count(&buffer), original);
+}
+
+#[test]
+fn distant_chunks_are_simplified_more() {
+    let settings = SimplificationSettings::default();
+
+    assert_eq!(settings.detail_level(0.0), 0);
+    assert_eq!(settings.detail_level(settings.near_distance), 0);
+    assert_eq!(settings.detail_level(settings.far_distance), DETAIL_LEVELS);
+    assert_eq!(
+        settings.detail_level(10.0 * settings.far_distance),
+        DETAIL_LEVELS
+    );
+
+    assert_eq!(settings.level_ratio(0), 1.0);
+    assert_eq!(settings.level_ratio(DETAIL_LEVELS), settings.target_ratio);
+
+    let mut previous = (0, 1.0);
+    for distance in (0..100).map(|i| i as f32 * settings.far_distance / 50.0) {
+        let level = settings.detail_level(distance);
+        let ratio = settings.level_ratio(level);
+        assert!(level >= previous.0 && ratio <= previous.1);
+        previous = (level, ratio);
+    }
+}
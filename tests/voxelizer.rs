@@ -0,0 +1,80 @@
+//! The voxelized meshes are negative inside and positive outside, and the grids that can't be
+//! allocated are refused.
+
+use bevy::prelude::*;
+use surface_nets_experiment::generation::MeshSdf;
+
+const VOXEL_SIZE: f32 = 0.25;
+
+/// Cube from -1 to 1 on every axis
+fn cube() -> (Vec<Vec3>, Vec<[u32; 3]>) {
+    let positions = (0..8)
+        .map(|i| {
+            Vec3::new((i & 1) as f32, ((i >> 1) & 1) as f32, ((i >> 2) & 1) as f32) * 2.0 - 1.0
+        })
+        .collect();
+    let triangles = vec![
+        [0, 2, 1],
+        [1, 2, 3],
+        [4, 5, 6],
+        [5, 7, 6],
+        [0, 1, 4],
+        [1, 5, 4],
+        [2, 6, 3],
+        [3, 6, 7],
+        [0, 4, 2],
+        [2, 4, 6],
+        [1, 3, 5],
+        [3, 7, 5],
+    ];
+    (positions, triangles)
+}
+
+#[test]
+fn cubes_are_negative_inside() {
+    let (positions, triangles) = cube();
+    let sdf = MeshSdf::voxelize(&positions, &triangles, VOXEL_SIZE).unwrap();
+
+    for p in [
+        Vec3::ZERO,
+        Vec3::new(0.9, 0.0, 0.0),
+        Vec3::new(-0.3, 0.7, -0.8),
+        Vec3::splat(0.95),
+    ] {
+        assert!(sdf.sample(p) < 0.0, "{p}");
+    }
+    for p in [
+        Vec3::new(1.2, 0.0, 0.0),
+        Vec3::new(0.0, -1.4, 0.3),
+        Vec3::splat(1.1),
+        Vec3::splat(10.0),
+    ] {
+        assert!(sdf.sample(p) > 0.0, "{p}");
+    }
+}
+
+#[test]
+fn distances_are_exact_near_the_surface() {
+    let (positions, triangles) = cube();
+    let sdf = MeshSdf::voxelize(&positions, &triangles, VOXEL_SIZE).unwrap();
+
+    for d in [-0.3, -0.1, 0.1, 0.3] {
+        for axis in [Vec3::X, Vec3::NEG_Y, Vec3::Z] {
+            let p = axis * (1.0 + d) + axis.any_orthogonal_vector() * 0.2;
+            assert!((sdf.sample(p) - d).abs() < 0.05, "{p}: {}", sdf.sample(p));
+        }
+    }
+}
+
+#[test]
+fn invalid_voxel_sizes_are_rejected() {
+    let (positions, triangles) = cube();
+
+    for voxel_size in [0.0, -0.5, f32::NAN, f32::INFINITY, 1e-4] {
+        assert!(
+            MeshSdf::voxelize(&positions, &triangles, voxel_size).is_err(),
+            "{voxel_size}"
+        );
+    }
+    assert!(MeshSdf::voxelize(&positions, &[], VOXEL_SIZE).is_err());
+}